@@ -0,0 +1,104 @@
+//! The case conventions of `#[query(implicit, rename_all = "...")]`, as in serde.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    /// Uses the field names as they are.
+    None,
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    pub(crate) const NAMES: &'static [&'static str] = &[
+        "lowercase",
+        "UPPERCASE",
+        "PascalCase",
+        "camelCase",
+        "snake_case",
+        "SCREAMING_SNAKE_CASE",
+        "kebab-case",
+        "SCREAMING-KEBAB-CASE",
+    ];
+
+    pub(crate) fn parse(rule: &str) -> Option<Self> {
+        Some(match rule {
+            "lowercase" => RenameRule::LowerCase,
+            "UPPERCASE" => RenameRule::UpperCase,
+            "PascalCase" => RenameRule::PascalCase,
+            "camelCase" => RenameRule::CamelCase,
+            "snake_case" => RenameRule::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnakeCase,
+            "kebab-case" => RenameRule::KebabCase,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebabCase,
+            _ => return None,
+        })
+    }
+
+    /// Renames a field name in `snake_case`.
+    pub(crate) fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::None | RenameRule::LowerCase | RenameRule::SnakeCase => field.to_string(),
+            RenameRule::UpperCase | RenameRule::ScreamingSnakeCase => field.to_ascii_uppercase(),
+            RenameRule::PascalCase => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            RenameRule::CamelCase => {
+                let pascal = RenameRule::PascalCase.apply(field);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => pascal,
+                }
+            }
+            RenameRule::KebabCase => field.replace('_', "-"),
+            RenameRule::ScreamingKebabCase => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rename() {
+        let field = "first_name_2";
+        let renamed: Vec<String> = RenameRule::NAMES
+            .iter()
+            .map(|name| RenameRule::parse(name).unwrap().apply(field))
+            .collect();
+        assert_eq!(
+            renamed,
+            [
+                "first_name_2",
+                "FIRST_NAME_2",
+                "FirstName2",
+                "firstName2",
+                "first_name_2",
+                "FIRST_NAME_2",
+                "first-name-2",
+                "FIRST-NAME-2",
+            ]
+        );
+        assert_eq!(RenameRule::None.apply("id"), "id");
+        assert_eq!(RenameRule::CamelCase.apply("id"), "id");
+    }
+}
@@ -0,0 +1,221 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+
+/// The generic parameters of the derived struct.
+///
+/// The generated seeds and visitors take the same parameters as the struct because the types of
+/// the queries may mention them.
+pub(crate) struct Generics {
+    /// The parameters without defaults, e.g. `'a`, `T: Clone`, or `const N: usize`.
+    params: Vec<TokenStream>,
+    /// The parameters of a seed or a visitor. The type and lifetime parameters outlive `'query`
+    /// because the types of the queries, e.g. `<Vec<&'a str> as Container>::Element`, may not
+    /// be normalized when checking the references to them.
+    seed_params: Vec<TokenStream>,
+    /// The parameters as arguments, e.g. `'a`, `T`, or `N`.
+    args: Vec<TokenStream>,
+    /// The predicates in the `where` clause of the struct.
+    predicates: Vec<TokenStream>,
+    /// `T: Deserialize<'de>` for the type parameters used in the fields deserialized with
+    /// `Deserialize`, and `'de: 'a` for the lifetimes used in any field, as serde does.
+    bounds: Vec<TokenStream>,
+    /// The type of a `PhantomData` that uses every type and lifetime parameter.
+    marker: Option<TokenStream>,
+}
+
+/// Returns whether `tokens` mention `ident`.
+fn mentions(tokens: TokenStream, ident: &syn::Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(i) => &i == ident,
+        TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+impl Generics {
+    /// `bounded_types` are the types of the fields whose type parameters need `Deserialize`.
+    pub(crate) fn new<'a, I, J>(generics: &syn::Generics, field_types: I, bounded_types: J) -> Self
+    where
+        I: IntoIterator<Item = &'a syn::Type>,
+        J: IntoIterator<Item = &'a syn::Type>,
+    {
+        let field_types: Vec<TokenStream> = field_types
+            .into_iter()
+            .map(ToTokens::to_token_stream)
+            .collect();
+        let bounded_types: Vec<TokenStream> = bounded_types
+            .into_iter()
+            .map(ToTokens::to_token_stream)
+            .collect();
+
+        let query_lifetime = syn::Lifetime::new("'query", proc_macro2::Span::call_site());
+        let mut params = vec![];
+        let mut seed_params = vec![];
+        let mut args = vec![];
+        let mut bounds = vec![];
+        let mut phantoms = vec![];
+        for param in generics.params.iter() {
+            let mut param = param.clone();
+            match &mut param {
+                syn::GenericParam::Type(ty) => {
+                    ty.eq_token = None;
+                    ty.default = None;
+                    let ident = &ty.ident;
+                    if bounded_types.iter().any(|t| mentions(t.clone(), ident)) {
+                        bounds.push(
+                            quote::quote!(#ident: serde_query::__priv::serde::Deserialize<'de>),
+                        );
+                    }
+                    args.push(ident.to_token_stream());
+                    phantoms.push(quote::quote!(fn() -> #ident));
+                }
+                syn::GenericParam::Lifetime(lifetime) => {
+                    let lifetime = &lifetime.lifetime;
+                    // the fields borrow from the input
+                    if field_types
+                        .iter()
+                        .any(|t| mentions(t.clone(), &lifetime.ident))
+                    {
+                        bounds.push(quote::quote!('de: #lifetime));
+                    }
+                    args.push(lifetime.to_token_stream());
+                    phantoms.push(quote::quote!(&#lifetime ()));
+                }
+                syn::GenericParam::Const(constant) => {
+                    constant.eq_token = None;
+                    constant.default = None;
+                    args.push(constant.ident.to_token_stream());
+                }
+            }
+            params.push(param.to_token_stream());
+            match &mut param {
+                syn::GenericParam::Type(ty) => {
+                    ty.colon_token.get_or_insert_with(Default::default);
+                    ty.bounds
+                        .push(syn::TypeParamBound::Lifetime(query_lifetime.clone()));
+                }
+                syn::GenericParam::Lifetime(lifetime) => {
+                    lifetime.colon_token.get_or_insert_with(Default::default);
+                    lifetime.bounds.push(query_lifetime.clone());
+                }
+                syn::GenericParam::Const(_) => {}
+            }
+            seed_params.push(param.to_token_stream());
+        }
+
+        let predicates = generics
+            .where_clause
+            .iter()
+            .flat_map(|where_clause| where_clause.predicates.iter())
+            .map(ToTokens::to_token_stream)
+            .collect();
+        let marker = (!phantoms.is_empty())
+            .then(|| quote::quote!(core::marker::PhantomData<(#(#phantoms,)*)>));
+
+        Self {
+            params,
+            seed_params,
+            args,
+            predicates,
+            bounds,
+            marker,
+        }
+    }
+
+    /// `<'query, ...>` in the declaration of a seed or a visitor.
+    pub(crate) fn decl(&self) -> TokenStream {
+        let params = &self.seed_params;
+        quote::quote!(<'query #(, #params)*>)
+    }
+
+    /// The `where` clause in the declaration of a seed or a visitor.
+    pub(crate) fn decl_where(&self) -> TokenStream {
+        if self.predicates.is_empty() {
+            return quote::quote!();
+        }
+        let predicates = &self.predicates;
+        quote::quote!(where #(#predicates,)*)
+    }
+
+    /// `<'query, 'de, ...>` in the implementation of a seed or a visitor.
+    pub(crate) fn impl_generics(&self) -> TokenStream {
+        let params = &self.seed_params;
+        quote::quote!(<'query, 'de #(, #params)*>)
+    }
+
+    /// `<'query, ...>` after the name of a seed or a visitor.
+    pub(crate) fn ty_generics(&self) -> TokenStream {
+        let args = &self.args;
+        quote::quote!(<'query #(, #args)*>)
+    }
+
+    /// The `where` clause in an implementation that deserializes the queried fields.
+    pub(crate) fn impl_where(&self) -> TokenStream {
+        if self.predicates.is_empty() && self.bounds.is_empty() {
+            return quote::quote!();
+        }
+        let predicates = self.predicates.iter().chain(self.bounds.iter());
+        quote::quote!(where #(#predicates,)*)
+    }
+
+    /// The marker field of a seed or a visitor, if the struct has type or lifetime parameters.
+    pub(crate) fn marker_field(&self) -> TokenStream {
+        match &self.marker {
+            Some(marker) => quote::quote!(__marker: #marker,),
+            None => quote::quote!(),
+        }
+    }
+
+    /// Initializes the marker field of a seed or a visitor.
+    pub(crate) fn marker_init(&self) -> TokenStream {
+        match &self.marker {
+            Some(_) => quote::quote!(__marker: core::marker::PhantomData,),
+            None => quote::quote!(),
+        }
+    }
+
+    /// `<'_, ...>` for naming a seed in a function body, or nothing if the struct is not generic.
+    pub(crate) fn elided_ty_generics(&self) -> TokenStream {
+        if self.args.is_empty() {
+            return quote::quote!();
+        }
+        let args = &self.args;
+        quote::quote!(<'_ #(, #args)*>)
+    }
+
+    /// `::<'_, ...>` for constructing a seed or a visitor, or nothing if the struct is not generic.
+    ///
+    /// The arguments cannot always be inferred from the fields, e.g. when a query does not mention
+    /// a type parameter.
+    pub(crate) fn turbofish(&self) -> TokenStream {
+        if self.args.is_empty() {
+            return quote::quote!();
+        }
+        let args = &self.args;
+        quote::quote!(::<'_ #(, #args)*>)
+    }
+
+    /// `<'de, ...>` in the implementation of `Deserialize` for the struct.
+    pub(crate) fn de_impl_generics(&self) -> TokenStream {
+        let params = &self.params;
+        quote::quote!(<'de #(, #params)*>)
+    }
+
+    /// `<...>` after the name of the struct, or nothing if the struct is not generic.
+    pub(crate) fn struct_ty_generics(&self) -> TokenStream {
+        if self.args.is_empty() {
+            return quote::quote!();
+        }
+        let args = &self.args;
+        quote::quote!(<#(#args),*>)
+    }
+
+    /// `<...>` in an implementation for the struct that does not deserialize it.
+    pub(crate) fn struct_impl_generics(&self) -> TokenStream {
+        if self.params.is_empty() {
+            return quote::quote!();
+        }
+        let params = &self.params;
+        quote::quote!(<#(#params),*>)
+    }
+}
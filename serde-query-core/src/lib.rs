@@ -1,15 +1,21 @@
+mod case;
+mod generics;
 mod node;
 mod parse_input;
+mod parse_jsonpath;
+mod parse_pointer;
 mod parse_query;
 mod query;
 
 #[cfg(test)]
 mod tests;
 
+use generics::Generics;
 use node::Node;
-use parse_input::parse_input;
+use parse_input::{parse_input, ParsedData, ParsedVariant};
 use proc_macro2::{Span, TokenStream};
 use proc_macro_error::Diagnostic;
+use query::{DeserializeWith, FieldQueries, PathSegment};
 use syn::DeriveInput;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,62 +34,117 @@ pub fn generate_derive(
     }
 
     let name = &input.ident;
-    let node = Node::from_queries(parse_input_result.queries.into_iter())?;
-    let mut stream = node.generate().map_err(|diagnostic| vec![diagnostic])?;
+    let field_types: Vec<&syn::Type> = match &input.data {
+        syn::Data::Struct(data) => data.fields.iter().map(|field| &field.ty).collect(),
+        syn::Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .map(|field| &field.ty)
+            .collect(),
+        syn::Data::Union(_) => unreachable!("parse_input rejects unions"),
+    };
+    // As in serde, the fields deserialized with a function or as a nested struct need no bounds.
+    let parsed_fields: Vec<&FieldQueries> = match &parse_input_result.data {
+        ParsedData::Struct(fields) => fields.fields.iter().collect(),
+        ParsedData::Enum { variants, .. } => variants
+            .iter()
+            .flat_map(|variant| variant.fields.fields.iter())
+            .collect(),
+    };
+    let bounded_types = parsed_fields
+        .into_iter()
+        .filter(|field| {
+            matches!(
+                field.deserialize_with,
+                DeserializeWith::Deserialize | DeserializeWith::Borrow
+            )
+        })
+        .map(|field| &field.ty);
+    let generics = Generics::new(&input.generics, field_types, bounded_types);
+    let impl_generics = generics.struct_impl_generics();
+    let ty_generics = generics.struct_ty_generics();
+    let decl_where = generics.decl_where();
+    let de_impl_generics = generics.de_impl_generics();
+    let impl_where = generics.impl_where();
 
-    // generate the root code
-    match target {
-        // generate DeserializeQuery and conversion traits
-        DeriveTarget::DeserializeQuery => {
-            let wrapper_ty = syn::Ident::new("__QueryWrapper", Span::call_site());
-
-            // Inherit visibility of the wrapped struct to avoid error E0446
-            // See: https://github.com/pandaman64/serde-query/issues/7
-            let vis = input.vis;
+    let wrapper_ty = syn::Ident::new("__QueryWrapper", Span::call_site());
+    let implementor_ty = match target {
+        DeriveTarget::DeserializeQuery => quote::quote!(#wrapper_ty #ty_generics),
+        DeriveTarget::Deserialize => quote::quote!(#name #ty_generics),
+    };
+    let construction = |value: TokenStream| match target {
+        DeriveTarget::DeserializeQuery => quote::quote!(#wrapper_ty(#value)),
+        DeriveTarget::Deserialize => value,
+    };
 
+    let (mut stream, deserialize_impl) = match parse_input_result.data {
+        ParsedData::Struct(fields) => {
+            let node = Node::from_queries(fields.queries.into_iter(), "")?;
+            let stream = node
+                .generate(&generics)
+                .map_err(|diagnostic| vec![diagnostic])?;
             let deserialize_impl = node.generate_deserialize(
-                name,
-                &wrapper_ty,
-                |value| quote::quote!(#wrapper_ty(#value)),
+                &fields.fields,
+                quote::quote!(#name),
+                implementor_ty,
+                &generics,
+                construction,
             );
+            (stream, deserialize_impl)
+        }
+        ParsedData::Enum { tag, variants } => {
+            generate_enum(name, tag, variants, implementor_ty, &generics, construction)?
+        }
+    };
 
-            stream.extend(quote::quote! {
-                #[repr(transparent)]
-                #vis struct #wrapper_ty (#name);
+    // generate the root code
+    stream.extend(deserialize_impl);
+    if target == DeriveTarget::DeserializeQuery {
+        // generate DeserializeQuery and conversion traits
 
-                #deserialize_impl
+        // Inherit visibility of the wrapped struct to avoid error E0446
+        // See: https://github.com/pandaman64/serde-query/issues/7
+        let vis = input.vis;
 
-                impl core::convert::From<#wrapper_ty> for #name {
-                    fn from(val: #wrapper_ty) -> Self {
-                        val.0
-                    }
+        stream.extend(quote::quote! {
+            #[repr(transparent)]
+            #vis struct #wrapper_ty #impl_generics (#name #ty_generics) #decl_where;
+
+            impl #impl_generics core::convert::From<#wrapper_ty #ty_generics> for #name #ty_generics #decl_where {
+                fn from(val: #wrapper_ty #ty_generics) -> Self {
+                    val.0
                 }
+            }
 
-                impl core::ops::Deref for #wrapper_ty {
-                    type Target = #name;
+            impl #impl_generics core::ops::Deref for #wrapper_ty #ty_generics #decl_where {
+                type Target = #name #ty_generics;
 
-                    fn deref(&self) -> &Self::Target {
-                        &self.0
-                    }
+                fn deref(&self) -> &Self::Target {
+                    &self.0
                 }
+            }
 
-                impl core::ops::DerefMut for #wrapper_ty {
-                    fn deref_mut(&mut self) -> &mut Self::Target {
-                        &mut self.0
-                    }
+            impl #impl_generics core::ops::DerefMut for #wrapper_ty #ty_generics #decl_where {
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    &mut self.0
                 }
-            });
+            }
+
+            impl #de_impl_generics serde_query::DeserializeQuery<'de> for #name #ty_generics #impl_where {
+                type Query = #wrapper_ty #ty_generics;
+            }
 
-            stream.extend(quote::quote! {
-                impl<'de> serde_query::DeserializeQuery<'de> for #name {
-                    type Query = #wrapper_ty;
+            impl #de_impl_generics serde_query::__priv::DeserializeNested<'de> for #name #ty_generics #impl_where {
+                fn deserialize_nested<D>(deserializer: D) -> core::result::Result<core::result::Result<Self, serde_query::__priv::Errors>, D::Error>
+                where
+                    D: serde_query::__priv::serde::de::Deserializer<'de>
+                {
+                    let result = <#wrapper_ty #ty_generics as serde_query::__priv::DeserializeNested<'de>>::deserialize_nested(deserializer)?;
+                    core::result::Result::Ok(result.map(|value| value.0))
                 }
-            });
-        }
-        DeriveTarget::Deserialize => {
-            let deserialize_impl = node.generate_deserialize(name, name, |value| value);
-            stream.extend(deserialize_impl);
-        }
+            }
+        });
     }
 
     // Cargo-culting serde. Possibly for scoping?
@@ -93,3 +154,150 @@ pub fn generate_derive(
         };
     })
 }
+
+/// Generates `Deserialize` for an enum.
+///
+/// Each variant is deserialized through a wrapper (e.g. `__Variant0`). Without a tag, the
+/// variants are tried in order on the buffered input, and the error lists the reason why each
+/// variant failed. With a tag, the variant is selected by the value of the tag.
+fn generate_enum(
+    name: &syn::Ident,
+    tag: Option<Vec<PathSegment>>,
+    variants: Vec<ParsedVariant>,
+    implementor_ty: TokenStream,
+    generics: &Generics,
+    construction: impl FnOnce(TokenStream) -> TokenStream,
+) -> Result<(TokenStream, TokenStream), Vec<Diagnostic>> {
+    let impl_generics = generics.struct_impl_generics();
+    let ty_generics = generics.struct_ty_generics();
+    let decl_where = generics.decl_where();
+    let de_impl_generics = generics.de_impl_generics();
+    let impl_where = generics.impl_where();
+
+    let mut stream = TokenStream::new();
+    let mut diagnostics = vec![];
+    let mut variant_tys = vec![];
+    let mut variant_names = vec![];
+    let mut tag_values = vec![];
+    for (index, variant) in variants.into_iter().enumerate() {
+        let namespace = format!("Variant{}", index);
+        let node = match Node::from_queries(variant.fields.queries.into_iter(), &namespace) {
+            Ok(node) => node,
+            Err(errors) => {
+                diagnostics.extend(errors);
+                continue;
+            }
+        };
+        match node.generate(generics) {
+            Ok(code) => stream.extend(code),
+            Err(diagnostic) => {
+                diagnostics.push(diagnostic);
+                continue;
+            }
+        }
+
+        let variant_ident = &variant.ident;
+        let variant_ty = quote::format_ident!("__{}", namespace);
+        let deserialize_impl = node.generate_deserialize(
+            &variant.fields.fields,
+            quote::quote!(#name::#variant_ident),
+            quote::quote!(#variant_ty #ty_generics),
+            generics,
+            |value| quote::quote!(#variant_ty(#value)),
+        );
+        stream.extend(quote::quote! {
+            #[repr(transparent)]
+            struct #variant_ty #impl_generics (#name #ty_generics) #decl_where;
+
+            #deserialize_impl
+        });
+        variant_tys.push(variant_ty);
+        variant_names.push(variant_ident.to_string());
+        tag_values.extend(variant.tag_value);
+    }
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    let name_string = name.to_string();
+    let construction = construction(quote::quote!(value.0));
+    let deserialize_impl = match tag {
+        Some(tag) => {
+            let tag = node::generate_path(&tag);
+            quote::quote! {
+                impl #de_impl_generics serde_query::__priv::serde::de::Deserialize<'de> for #implementor_ty #impl_where {
+                    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+                    where
+                        D: serde_query::__priv::serde::de::Deserializer<'de>
+                    {
+                        serde_query::__priv::deserialize_tagged(deserializer)
+                    }
+                }
+
+                impl #de_impl_generics serde_query::__priv::Tagged<'de> for #implementor_ty #impl_where {
+                    const NAME: &'static str = #name_string;
+                    const TAG: &'static [serde_query::__priv::PathSegment] = &[#(#tag),*];
+
+                    fn deserialize_variant<D>(tag: &str, deserializer: D) -> core::result::Result<Self, D::Error>
+                    where
+                        D: serde_query::__priv::serde::de::Deserializer<'de>
+                    {
+                        match tag {
+                            #(
+                                #tag_values => {
+                                    let value = <#variant_tys #ty_generics as serde_query::__priv::serde::de::Deserialize<'de>>::deserialize(deserializer)?;
+                                    core::result::Result::Ok(#construction)
+                                }
+                            )*
+                            _ => core::result::Result::Err(
+                                <D::Error as serde_query::__priv::serde::de::Error>::unknown_variant(tag, &[#(#tag_values),*])
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+        None => quote::quote! {
+            impl #de_impl_generics serde_query::__priv::serde::de::Deserialize<'de> for #implementor_ty #impl_where {
+                fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+                where
+                    D: serde_query::__priv::serde::de::Deserializer<'de>
+                {
+                    let content = <serde_query::__priv::Content<'de> as serde_query::__priv::serde::de::Deserialize<'de>>::deserialize(deserializer)?;
+                    let errors = [
+                        #(
+                            (
+                                #variant_names,
+                                match <#variant_tys #ty_generics as serde_query::__priv::serde::de::Deserialize<'de>>::deserialize(
+                                    serde_query::__priv::ContentDeserializer::<D::Error>::new(content.clone()),
+                                ) {
+                                    core::result::Result::Ok(value) => return core::result::Result::Ok(#construction),
+                                    core::result::Result::Err(e) => e,
+                                },
+                            ),
+                        )*
+                    ];
+                    core::result::Result::Err(
+                        <D::Error as serde_query::__priv::serde::de::Error>::custom(serde_query::__priv::VariantErrors::new(#name_string, &errors))
+                    )
+                }
+            }
+        },
+    };
+
+    // The errors of the variants are reported through `Deserialize` as a whole.
+    let deserialize_impl = quote::quote! {
+        #deserialize_impl
+
+        impl #de_impl_generics serde_query::__priv::DeserializeNested<'de> for #implementor_ty #impl_where {
+            fn deserialize_nested<D>(deserializer: D) -> core::result::Result<core::result::Result<Self, serde_query::__priv::Errors>, D::Error>
+            where
+                D: serde_query::__priv::serde::de::Deserializer<'de>
+            {
+                <Self as serde_query::__priv::serde::de::Deserialize<'de>>::deserialize(deserializer).map(core::result::Result::Ok)
+            }
+        }
+    };
+
+    Ok((stream, deserialize_impl))
+}
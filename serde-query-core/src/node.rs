@@ -1,34 +1,148 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use proc_macro2::{Literal, TokenStream};
 use proc_macro_error::{diagnostic, Diagnostic, Level};
+use syn::ext::IdentExt;
 
-use crate::query::{Query, QueryFragment, QueryId};
+use crate::{
+    generics::Generics,
+    query::{
+        self, Comparison, DeserializeWith, FieldQueries, Function, PathSegment, Predicate, Query,
+        QueryFragment, QueryId,
+    },
+};
 
-#[derive(Debug, Default)]
-struct Env {
+#[derive(Debug)]
+struct Env<'a> {
+    /// Distinguishes the nodes of the enum variants, e.g. `Variant0`.
+    namespace: &'a str,
     node_count: usize,
 }
 
-impl Env {
-    fn new() -> Self {
-        Self::default()
+impl<'a> Env<'a> {
+    fn new(namespace: &'a str) -> Self {
+        Self {
+            namespace,
+            node_count: 0,
+        }
     }
 
     fn new_node_name(&mut self) -> String {
         let node_id = self.node_count;
         self.node_count += 1;
-        format!("Node{node_id}")
+        format!("{}Node{node_id}", self.namespace)
+    }
+}
+
+fn slice_string(start: usize, end: Option<usize>) -> String {
+    match end {
+        Some(end) => format!("[{}:{}]", start, end),
+        None => format!("[{}:]", start),
+    }
+}
+
+/// The minimum length of a sequence that has an element at `index`.
+fn min_sequence_len(index: isize) -> usize {
+    if index < 0 {
+        index.unsigned_abs()
+    } else {
+        index as usize + 1
     }
 }
 
+/// Generates `serde_query::__priv::PathSegment`s for the path.
+pub(crate) fn generate_path(path: &[PathSegment]) -> Vec<TokenStream> {
+    path.iter()
+        .map(|segment| match segment {
+            PathSegment::Field { name, .. } => {
+                quote::quote!(serde_query::__priv::PathSegment::Field(#name))
+            }
+            PathSegment::Index(index) => {
+                quote::quote!(serde_query::__priv::PathSegment::Index(#index))
+            }
+        })
+        .collect()
+}
+
+/// Generates the arguments of `serde_query::__priv::select` for the predicate.
+fn generate_predicate(predicate: &Predicate) -> TokenStream {
+    let path = generate_path(&predicate.path);
+    let comparison = match &predicate.comparison {
+        None => quote::quote!(core::option::Option::None),
+        Some((comparison, literal)) => {
+            let comparison = match comparison {
+                Comparison::Eq => quote::quote!(Eq),
+                Comparison::Ne => quote::quote!(Ne),
+                Comparison::Lt => quote::quote!(Lt),
+                Comparison::Le => quote::quote!(Le),
+                Comparison::Gt => quote::quote!(Gt),
+                Comparison::Ge => quote::quote!(Ge),
+            };
+            let literal = match literal {
+                query::Literal::Null => quote::quote!(Null),
+                query::Literal::Bool(b) => quote::quote!(Bool(#b)),
+                // integers are compared exactly
+                query::Literal::Number(n) => match n.parse::<i128>() {
+                    Ok(n) => {
+                        let n = Literal::i128_unsuffixed(n);
+                        quote::quote!(Integer(#n))
+                    }
+                    Err(_) => {
+                        let n = Literal::f64_unsuffixed(n.parse().unwrap());
+                        quote::quote!(Number(#n))
+                    }
+                },
+                query::Literal::String(s) => quote::quote!(String(#s)),
+            };
+            quote::quote! {
+                core::option::Option::Some((
+                    serde_query::__priv::Comparison::#comparison,
+                    serde_query::__priv::Literal::#literal,
+                ))
+            }
+        }
+    };
+    quote::quote!(&[#(#path),*], #comparison)
+}
+
 #[derive(Debug)]
 enum NodeKind {
     None,
-    Accept,
-    Field { fields: BTreeMap<String, Node> },
-    IndexArray { indices: BTreeMap<usize, Node> },
-    CollectArray { child: Box<Node> },
+    Accept {
+        /// How to deserialize the value for each query.
+        deserialize_with: BTreeMap<QueryId, DeserializeWith>,
+    },
+    Field {
+        fields: BTreeMap<String, Node>,
+    },
+    Variant {
+        variants: BTreeMap<String, Node>,
+    },
+    IndexArray {
+        indices: BTreeMap<isize, Node>,
+    },
+    CollectArray {
+        child: Box<Node>,
+    },
+    CollectMap {
+        child: Box<Node>,
+    },
+    SliceArray {
+        start: usize,
+        end: Option<usize>,
+        child: Box<Node>,
+    },
+    RecursiveDescent {
+        name: String,
+        child: Box<Node>,
+    },
+    Select {
+        branches: BTreeMap<Predicate, Node>,
+    },
+    Function {
+        /// The queries for each function. The queries of a function share the result.
+        functions: BTreeMap<Function, Vec<QueryId>>,
+    },
 }
 
 impl NodeKind {
@@ -60,10 +174,25 @@ impl NodeKind {
         let this = std::mem::replace(self, Self::None);
         *self = match (this, other) {
             (NodeKind::None, other) => other,
-            (NodeKind::Accept, NodeKind::Accept) => NodeKind::Accept,
+            (
+                NodeKind::Accept {
+                    mut deserialize_with,
+                },
+                NodeKind::Accept {
+                    deserialize_with: other,
+                },
+            ) => {
+                deserialize_with.extend(other);
+                NodeKind::Accept { deserialize_with }
+            }
             (NodeKind::Field { fields }, NodeKind::Field { fields: other }) => NodeKind::Field {
                 fields: Self::merge_trees(fields, other)?,
             },
+            (NodeKind::Variant { variants }, NodeKind::Variant { variants: other }) => {
+                NodeKind::Variant {
+                    variants: Self::merge_trees(variants, other)?,
+                }
+            }
             (NodeKind::IndexArray { indices }, NodeKind::IndexArray { indices: other }) => {
                 NodeKind::IndexArray {
                     indices: Self::merge_trees(indices, other)?,
@@ -73,17 +202,86 @@ impl NodeKind {
                 child.merge(*other)?;
                 NodeKind::CollectArray { child }
             }
+            (NodeKind::CollectMap { mut child }, NodeKind::CollectMap { child: other }) => {
+                child.merge(*other)?;
+                NodeKind::CollectMap { child }
+            }
+            (
+                NodeKind::SliceArray {
+                    start,
+                    end,
+                    mut child,
+                },
+                NodeKind::SliceArray {
+                    start: other_start,
+                    end: other_end,
+                    child: other,
+                },
+            ) if start == other_start && end == other_end => {
+                child.merge(*other)?;
+                NodeKind::SliceArray { start, end, child }
+            }
+            (
+                NodeKind::RecursiveDescent { name, mut child },
+                NodeKind::RecursiveDescent {
+                    name: other_name,
+                    child: other,
+                },
+            ) if name == other_name => {
+                child.merge(*other)?;
+                NodeKind::RecursiveDescent { name, child }
+            }
+            (NodeKind::Function { mut functions }, NodeKind::Function { functions: other }) => {
+                for (function, ids) in other.into_iter() {
+                    functions.entry(function).or_default().extend(ids);
+                }
+                NodeKind::Function { functions }
+            }
+            (NodeKind::Select { branches }, NodeKind::Select { branches: other }) => {
+                NodeKind::Select {
+                    branches: Self::merge_trees(branches, other)?,
+                }
+            }
             (this, other) => {
-                let self_ident = self_query.expect("This node must have at least one query because the kind is not NodeKind::None").ident();
-                let other_ident = other_query.ident();
+                let self_query = self_query.expect(
+                    "This node must have at least one query because the kind is not NodeKind::None",
+                );
+                // A nested struct deserializes the whole value, so the other queries cannot look inside it.
+                let nested = |kind: &NodeKind| match kind {
+                    NodeKind::Accept { deserialize_with } => deserialize_with
+                        .iter()
+                        .find(|(_, with)| matches!(with, DeserializeWith::Nested))
+                        .map(|(id, _)| id.clone()),
+                    _ => None,
+                };
+                let conflict = match (nested(&this), nested(&other)) {
+                    (Some(nested), _) if !matches!(other, NodeKind::Accept { .. }) => {
+                        Some((nested, other_query))
+                    }
+                    (_, Some(nested)) if !matches!(this, NodeKind::Accept { .. }) => {
+                        Some((nested, self_query))
+                    }
+                    _ => None,
+                };
+                if let Some((nested, query)) = conflict {
+                    return Err(diagnostic!(
+                        query.ident(),
+                        Level::Error,
+                        "Conflicting query at '{}'. The field '{}' mounts a nested struct here, so the query for field '{}' cannot look inside it.",
+                        prefix,
+                        nested.field(),
+                        query.field();
+                        help = "move the query into the struct of the field '{}'", nested.field()
+                    ));
+                }
                 return Err(diagnostic!(
-                    self_ident,
+                    self_query.ident(),
                     Level::Error,
                     "Conflicting query at '{}'. The query for field '{}' expects {} while the query for field '{}' expects {}.",
                     prefix,
-                    self_ident,
+                    self_query.field(),
                     this.descripion(),
-                    other_ident,
+                    other_query.field(),
                     other.descripion()
                 ));
             }
@@ -91,12 +289,23 @@ impl NodeKind {
         Ok(())
     }
 
-    fn descripion(&self) -> &str {
+    fn descripion(&self) -> String {
         match self {
-            NodeKind::None => "none",
-            NodeKind::Accept => "a value here",
-            NodeKind::Field { .. } => "a struct",
-            NodeKind::IndexArray { .. } | NodeKind::CollectArray { .. } => "a sequence",
+            NodeKind::None => "none".into(),
+            NodeKind::Accept { .. } => "a value here".into(),
+            NodeKind::Field { .. } => "a struct".into(),
+            NodeKind::Variant { .. } => "an enum".into(),
+            NodeKind::IndexArray { .. } => "a sequence".into(),
+            NodeKind::CollectArray { .. } => "a sequence or a map".into(),
+            NodeKind::CollectMap { .. } => "a map".into(),
+            NodeKind::SliceArray { start, end, .. } => {
+                format!("a slice '{}' of a sequence", slice_string(*start, *end))
+            }
+            NodeKind::RecursiveDescent { name, .. } => {
+                format!("fields named '{}' at any depth", name)
+            }
+            NodeKind::Select { .. } => "a filter".into(),
+            NodeKind::Function { .. } => "a built-in function".into(),
         }
     }
 }
@@ -106,6 +315,13 @@ pub(crate) struct Node {
     name: String,
     // map of (id, ty)
     queries: BTreeMap<QueryId, TokenStream>,
+    /// Queries that evaluate to `None` when the value at this node is missing or null.
+    optional: BTreeSet<QueryId>,
+    /// Queries that evaluate to an empty container when the value at this node is missing or null.
+    empty: BTreeSet<QueryId>,
+    /// Queries whose slots are left empty when the value at this node is missing, so that the
+    /// fields take their defaults.
+    defaults: BTreeSet<QueryId>,
     kind: NodeKind,
 
     // fields for diagnostics
@@ -114,23 +330,45 @@ pub(crate) struct Node {
 }
 
 impl Node {
+    /// A node for `queries` that are neither optional nor defaulted at this node.
+    fn new(
+        name: String,
+        queries: BTreeMap<QueryId, TokenStream>,
+        kind: NodeKind,
+        prefix: String,
+    ) -> Self {
+        Self {
+            name,
+            queries,
+            optional: BTreeSet::new(),
+            empty: BTreeSet::new(),
+            defaults: BTreeSet::new(),
+            kind,
+            prefix,
+        }
+    }
+
+    /// Builds the tree of the queries. The names of the generated types start with `namespace`.
     pub(crate) fn from_queries<I: Iterator<Item = Query>>(
         queries: I,
+        namespace: &str,
     ) -> Result<Node, Vec<Diagnostic>> {
         let mut diagnostics = vec![];
-        let mut env = Env::new();
-        let mut node = Node {
-            name: env.new_node_name(),
-            queries: BTreeMap::new(),
-            kind: NodeKind::None,
-            prefix: String::from("."),
-        };
+        let mut env = Env::new(namespace);
+        let mut node = Node::new(
+            env.new_node_name(),
+            BTreeMap::new(),
+            NodeKind::None,
+            String::from("."),
+        );
         for query in queries {
             if let Err(diagnostic) = node.merge(Node::from_query(
                 &mut env,
                 query.id,
                 query.fragment,
                 query.ty,
+                query.deserialize_with,
+                query.default,
                 String::new(),
             )) {
                 diagnostics.push(diagnostic);
@@ -149,15 +387,44 @@ impl Node {
         id: QueryId,
         fragment: QueryFragment,
         ty: TokenStream,
+        deserialize_with: DeserializeWith,
+        default: bool,
         prefix: String,
     ) -> Self {
-        let name = env.new_node_name();
-        match fragment {
-            QueryFragment::Accept => Self {
-                name,
-                queries: BTreeMap::from_iter([(id, ty)]),
-                kind: NodeKind::Accept,
+        // '?' does not introduce a new node. It marks the node for the rest of the query instead.
+        if let QueryFragment::Optional { rest } = fragment {
+            let mut node = Self::from_query(
+                env,
+                id.clone(),
+                *rest,
+                ty,
+                deserialize_with,
+                default,
+                prefix,
+            );
+            node.optional.insert(id);
+            return node;
+        }
+        // Likewise, '?' after a collection marks the node of the collection.
+        if let QueryFragment::OptionalCollection { rest } = fragment {
+            let mut node = Self::from_query(
+                env,
+                id.clone(),
+                *rest,
+                ty,
+                deserialize_with,
+                default,
                 prefix,
+            );
+            node.empty.insert(id);
+            return node;
+        }
+
+        let name = env.new_node_name();
+        let defaulted = default.then(|| id.clone());
+        let kind = match fragment {
+            QueryFragment::Accept => NodeKind::Accept {
+                deserialize_with: BTreeMap::from_iter([(id.clone(), deserialize_with)]),
             },
             QueryFragment::Field {
                 name: field_name,
@@ -169,15 +436,17 @@ impl Node {
                 } else {
                     format!("{}.{}", prefix, field_name)
                 };
-                let child = Self::from_query(env, id.clone(), *rest, ty.clone(), rest_prefix);
-                let kind = NodeKind::Field {
+                let child = Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    ty.clone(),
+                    deserialize_with,
+                    default,
+                    rest_prefix,
+                );
+                NodeKind::Field {
                     fields: BTreeMap::from_iter([(field_name, child)]),
-                };
-                Self {
-                    name,
-                    queries: BTreeMap::from_iter([(id, ty)]),
-                    kind,
-                    prefix,
                 }
             }
             QueryFragment::IndexArray { index, rest } => {
@@ -186,16 +455,12 @@ impl Node {
                     id.clone(),
                     *rest,
                     ty.clone(),
+                    deserialize_with,
+                    default,
                     format!("{}.[{}]", prefix, index),
                 );
-                let kind = NodeKind::IndexArray {
+                NodeKind::IndexArray {
                     indices: BTreeMap::from_iter([(index, child)]),
-                };
-                Self {
-                    name,
-                    queries: BTreeMap::from_iter([(id, ty)]),
-                    kind,
-                    prefix,
                 }
             }
             QueryFragment::CollectArray { rest } => {
@@ -205,17 +470,100 @@ impl Node {
                     id.clone(),
                     *rest,
                     element_ty,
+                    deserialize_with,
+                    // The default is for the whole container, not for the elements.
+                    false,
                     format!("{}.[]", prefix),
                 ));
-                let kind = NodeKind::CollectArray { child };
-                Self {
-                    name,
-                    queries: BTreeMap::from_iter([(id, ty)]),
-                    kind,
-                    prefix,
+                NodeKind::CollectArray { child }
+            }
+            QueryFragment::CollectMap { rest } => {
+                let value_ty = quote::quote!(<#ty as serde_query::__priv::MapContainer>::Value);
+                let child = Box::new(Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    value_ty,
+                    deserialize_with,
+                    false,
+                    format!("{}.{{}}", prefix),
+                ));
+                NodeKind::CollectMap { child }
+            }
+            QueryFragment::SliceArray { start, end, rest } => {
+                let element_ty = quote::quote!(<#ty as serde_query::__priv::Container>::Element);
+                let child = Box::new(Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    element_ty,
+                    deserialize_with,
+                    false,
+                    format!("{}.{}", prefix, slice_string(start, end)),
+                ));
+                NodeKind::SliceArray { start, end, child }
+            }
+            QueryFragment::Variant {
+                name: variant_name,
+                rest,
+            } => {
+                let rest_prefix = format!("{}!{}", prefix, variant_name);
+                let child = Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    ty.clone(),
+                    deserialize_with,
+                    default,
+                    rest_prefix,
+                );
+                NodeKind::Variant {
+                    variants: BTreeMap::from_iter([(variant_name, child)]),
                 }
             }
-        }
+            QueryFragment::RecursiveField {
+                name: field_name,
+                rest,
+            } => {
+                let element_ty = quote::quote!(<#ty as serde_query::__priv::Container>::Element);
+                let child = Box::new(Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    element_ty,
+                    deserialize_with,
+                    false,
+                    format!("{}..{}", prefix, field_name),
+                ));
+                NodeKind::RecursiveDescent {
+                    name: field_name,
+                    child,
+                }
+            }
+            QueryFragment::Select { predicate, rest } => {
+                let child = Self::from_query(
+                    env,
+                    id.clone(),
+                    *rest,
+                    ty.clone(),
+                    deserialize_with,
+                    default,
+                    format!("{} | select({})", prefix, predicate),
+                );
+                NodeKind::Select {
+                    branches: BTreeMap::from_iter([(predicate, child)]),
+                }
+            }
+            QueryFragment::Function { function } => NodeKind::Function {
+                functions: BTreeMap::from_iter([(function, vec![id.clone()])]),
+            },
+            QueryFragment::Optional { .. } | QueryFragment::OptionalCollection { .. } => {
+                unreachable!()
+            }
+        };
+        let mut node = Self::new(name, BTreeMap::from_iter([(id, ty)]), kind, prefix);
+        node.defaults.extend(defaulted);
+        node
     }
 
     fn merge(&mut self, other: Self) -> Result<(), Diagnostic> {
@@ -224,7 +572,10 @@ impl Node {
 
         self.kind
             .merge(other.kind, &self.prefix, self_query, other_query)?;
-        self.queries.extend(other.queries.into_iter());
+        self.queries.extend(other.queries);
+        self.optional.extend(other.optional);
+        self.empty.extend(other.empty);
+        self.defaults.extend(other.defaults);
         Ok(())
     }
 
@@ -248,78 +599,375 @@ impl Node {
         self.queries.keys().map(QueryId::ident).collect()
     }
 
+    fn query_fields(&self) -> Vec<&str> {
+        self.queries.keys().map(QueryId::field).collect()
+    }
+
     fn query_types(&self) -> Vec<&TokenStream> {
         self.queries.values().collect()
     }
 
-    fn missing_fields_error_triple<K: Clone + Ord>(
-        children: &BTreeMap<K, Node>,
-    ) -> (Vec<K>, Vec<syn::Ident>, Vec<String>) {
-        let mut keys = vec![];
-        let mut idents = vec![];
-        let mut ident_strings = vec![];
+    /// The value of a query when the value at this node is missing or null, if the query allows it.
+    ///
+    /// Optional queries evaluate to `None`, and optional collections evaluate to an empty container.
+    fn absent_value(&self, id: &QueryId) -> Option<TokenStream> {
+        if self.optional.contains(id) {
+            Some(quote::quote!(core::result::Result::Ok(
+                core::option::Option::None
+            )))
+        } else if self.empty.contains(id) {
+            let ty = &self.queries[id];
+            Some(quote::quote! {
+                core::result::Result::Ok(<#ty as serde_query::__priv::Container>::empty())
+            })
+        } else {
+            None
+        }
+    }
 
-        for (field, node) in children.iter() {
+    /// Generates code that fills the slots of queries whose child was not found.
+    ///
+    /// Optional queries evaluate to `None` or an empty container, and the slots of queries with defaults are left empty.
+    /// The other queries report `message(key)`.
+    fn missing_children_fallback<K, F>(
+        children: &BTreeMap<K, Node>,
+        prefix: &str,
+        message: F,
+    ) -> Vec<TokenStream>
+    where
+        F: Fn(&K) -> String,
+    {
+        let mut fallback = vec![];
+
+        for (key, node) in children.iter() {
+            let message = message(key);
             for id in node.queries.keys() {
-                keys.push(field.clone());
-                idents.push(id.ident().clone());
-                ident_strings.push(id.ident().to_string());
+                let absent = node.absent_value(id);
+                if node.defaults.contains(id) && absent.is_none() {
+                    continue;
+                }
+                let query_name = id.ident();
+                let value = if let Some(absent) = absent {
+                    absent
+                } else {
+                    let query_name_string = id.field();
+                    quote::quote! {
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::borrowed(
+                                #query_name_string,
+                                #prefix,
+                                #message,
+                            )
+                        )
+                    }
+                };
+                fallback.push(quote::quote! {
+                    if self.#query_name.is_none() {
+                        *self.#query_name = core::option::Option::Some(#value);
+                    }
+                });
             }
         }
 
-        (keys, idents, ident_strings)
+        fallback
     }
 
-    pub(crate) fn generate(&self) -> Result<TokenStream, Diagnostic> {
-        Ok(match &self.kind {
-            NodeKind::Accept => {
-                if self.queries.len() > 1 {
-                    let (first_ident, second_ident) = {
-                        let mut keys = self.queries.keys();
-                        let first = keys.next().unwrap();
-                        let second = keys.next().unwrap();
-                        (first.ident(), second.ident())
-                    };
-                    return Err(diagnostic!(
-                        first_ident,
-                        Level::Error,
-                        "Cannot use the same query for two or more fields: '{}', '{}'",
-                        first_ident,
-                        second_ident,
-                    ));
+    /// Generates `visit_enum`, which steps into the child named after the variant.
+    ///
+    /// If the variant has no child, the queries of the children report the actual variant before the payload is touched.
+    /// The payload is skipped as a newtype variant only if every query allows the variant to be absent,
+    /// as the kind of the variant is unknown.
+    fn generate_visit_enum(
+        children: &BTreeMap<String, Node>,
+        prefix: &str,
+        generics: &Generics,
+    ) -> TokenStream {
+        let marker_init = generics.marker_init();
+        let turbofish = generics.turbofish();
+
+        let variant_arms = children.iter().map(|(variant, node)| {
+            let deserialize_seed_ty = node.deserialize_seed_ty();
+            let query_names = node.query_names();
+            let variant_deserializer = match node.kind {
+                NodeKind::Field { .. } => {
+                    quote::quote!(serde_query::__priv::VariantDeserializer::fields(variant))
                 }
-                let (query_id, query_type) = self.queries.first_key_value().unwrap();
-                let query_name = query_id.ident();
+                _ => quote::quote!(serde_query::__priv::VariantDeserializer::new(variant)),
+            };
+            quote::quote! {
+                #variant => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        #deserialize_seed_ty #turbofish {
+                            #marker_init
+                            #(
+                                #query_names: &mut *self.#query_names,
+                            )*
+                        },
+                        #variant_deserializer,
+                    )?;
+                }
+            }
+        });
 
-                let deserialize_seed_ty = self.deserialize_seed_ty();
+        // The queries that fail on any other variant.
+        let required: Vec<_> = children
+            .iter()
+            .flat_map(|(variant, node)| {
+                node.queries
+                    .keys()
+                    .filter(|id| node.absent_value(id).is_none() && !node.defaults.contains(*id))
+                    .map(move |id| {
+                        let query_name_string = id.field();
+                        quote::quote! {
+                            core::option::Option::Some(serde_query::__priv::Error::owned(
+                                #query_name_string,
+                                #prefix,
+                                serde_query::__priv::variant_mismatch(#variant, &name),
+                            ))
+                        }
+                    })
+            })
+            .collect();
+        let mismatch = if required.is_empty() {
+            quote::quote! {
+                serde_query::__priv::serde::de::VariantAccess::newtype_variant::<serde_query::__priv::serde::de::IgnoredAny>(variant)?;
+            }
+        } else {
+            quote::quote! {
+                let errors = [
+                    #(
+                        #required,
+                    )*
+                ];
+                return core::result::Result::Err(
+                    <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                        serde_query::__priv::Errors::new(errors)
+                    )
+                );
+            }
+        };
+
+        // The queries for the other variants report the actual variant, as a missing field does.
+        let variant_fallback = children.iter().map(|(variant, node)| {
+            let fallback = node.queries.keys().filter_map(move |id| {
+                let absent = node.absent_value(id);
+                if node.defaults.contains(id) && absent.is_none() {
+                    return None;
+                }
+                let query_name = id.ident();
+                let value = if let Some(absent) = absent {
+                    absent
+                } else {
+                    let query_name_string = id.field();
+                    quote::quote! {
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                #query_name_string,
+                                #prefix,
+                                serde_query::__priv::variant_mismatch(#variant, &name),
+                            )
+                        )
+                    }
+                };
+                Some(quote::quote! {
+                    if self.#query_name.is_none() {
+                        *self.#query_name = core::option::Option::Some(#value);
+                    }
+                })
+            });
+            quote::quote! {
+                if name.0 != #variant {
+                    #(#fallback)*
+                }
+            }
+        });
+
+        quote::quote! {
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde_query::__priv::serde::de::EnumAccess<'de>,
+            {
+                let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+                match name.0.as_str() {
+                    #(#variant_arms)*
+                    _ => {
+                        #mismatch
+                    }
+                }
+                #(#variant_fallback)*
+                core::result::Result::Ok(())
+            }
+        }
+    }
+
+    /// Generates code that passes `visitor` to `deserializer` using `method`.
+    ///
+    /// If some queries at this node are optional, a null value is accepted as well.
+    /// In that case, the optional queries evaluate to `None` or an empty container, and the other
+    /// queries report an error.
+    fn deserialize_visitor(&self, method: &str, expecting: &str) -> TokenStream {
+        // The variants of `!Variant` steps at this node.
+        let variants = match &self.kind {
+            NodeKind::Variant { variants } => variants.keys().collect(),
+            _ => vec![],
+        };
+        if self.optional.is_empty() && self.empty.is_empty() {
+            if method == "enum" {
+                return quote::quote! {
+                    deserializer.deserialize_enum("", &[#(#variants),*], visitor)?;
+                };
+            }
+            if method == "seq_or_map" {
+                return quote::quote! {
+                    serde_query::__priv::deserialize_seq_or_map(deserializer, visitor)?;
+                };
+            }
+            if method == "map_or_enum" {
+                return quote::quote! {
+                    serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
+                };
+            }
+            let method = quote::format_ident!("deserialize_{}", method);
+            return quote::quote! {
+                deserializer.#method(visitor)?;
+            };
+        }
+
+        let shape = match method {
+            "map" => quote::quote!(serde_query::__priv::Shape::Map),
+            "seq" => quote::quote!(serde_query::__priv::Shape::Seq),
+            "seq_or_map" => quote::quote!(serde_query::__priv::Shape::SeqOrMap),
+            "map_or_enum" => quote::quote!(serde_query::__priv::Shape::MapOrEnum),
+            "enum" => quote::quote!(serde_query::__priv::Shape::Enum(&[#(#variants),*])),
+            _ => unreachable!("unknown deserialize method: {}", method),
+        };
+        let prefix = &self.prefix;
+        let null_message = format!("invalid type: null, expected {}", expecting);
+        let null_fallback = self.queries.keys().map(|id| {
+            let query_name = id.ident();
+            let value = if let Some(absent) = self.absent_value(id) {
+                absent
+            } else {
+                let query_name_string = id.field();
+                quote::quote! {
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            #query_name_string,
+                            #prefix,
+                            #null_message,
+                        )
+                    )
+                }
+            };
+            quote::quote! {
+                *self.#query_name = core::option::Option::Some(#value);
+            }
+        });
+
+        quote::quote! {
+            if serde_query::__priv::deserialize_option(deserializer, #shape, visitor)?.is_none() {
+                #(#null_fallback)*
+                return core::result::Result::Ok(());
+            }
+        }
+    }
 
-                let field = query_name.to_string();
+    pub(crate) fn generate(&self, generics: &Generics) -> Result<TokenStream, Diagnostic> {
+        let seed_generics = generics.decl();
+        let seed_where = generics.decl_where();
+        let impl_generics = generics.impl_generics();
+        let ty_generics = generics.ty_generics();
+        let impl_where = generics.impl_where();
+        let marker = generics.marker_field();
+        let marker_init = generics.marker_init();
+        let turbofish = generics.turbofish();
+        Ok(match &self.kind {
+            NodeKind::Accept { deserialize_with } => {
+                let deserialize_seed_ty = self.deserialize_seed_ty();
+                let query_names = self.query_names();
+                let query_types = self.query_types();
                 let prefix = &self.prefix;
 
+                let results: Vec<TokenStream> = self
+                    .queries
+                    .iter()
+                    .map(|(query_id, query_type)| {
+                        let field = query_id.field();
+                        let deserialize = match &deserialize_with[query_id] {
+                            DeserializeWith::Deserialize => {
+                                quote::quote!(<#query_type as serde_query::__priv::serde::Deserialize<'de>>::deserialize(deserializer))
+                            }
+                            DeserializeWith::Borrow => {
+                                quote::quote!(<#query_type as serde_query::__priv::DeserializeBorrowed<'de>>::deserialize_borrowed(deserializer))
+                            }
+                            DeserializeWith::Function(function) => {
+                                quote::quote!(#function(deserializer))
+                            }
+                            DeserializeWith::Nested => {
+                                return quote::quote! {
+                                    match <#query_type as serde_query::__priv::DeserializeNested<'de>>::deserialize_nested(deserializer) {
+                                        core::result::Result::Ok(core::result::Result::Ok(v)) => core::result::Result::Ok(v),
+                                        core::result::Result::Ok(core::result::Result::Err(errors)) => core::result::Result::Err(
+                                            serde_query::__priv::Error::nested(#field, #prefix, errors)
+                                        ),
+                                        core::result::Result::Err(e) => core::result::Result::Err(
+                                            serde_query::__priv::Error::owned(#field, #prefix, e.to_string())
+                                        ),
+                                    }
+                                };
+                            }
+                        };
+                        quote::quote! {
+                            match #deserialize {
+                                core::result::Result::Ok(v) => core::result::Result::Ok(v),
+                                core::result::Result::Err(e) => core::result::Result::Err(
+                                    serde_query::__priv::Error::owned(#field, #prefix, e.to_string())
+                                ),
+                            }
+                        }
+                    })
+                    .collect();
+
+                // The fields with the same query deserialize the buffered value in turn.
+                let body = if results.len() == 1 {
+                    quote::quote! {
+                        #(
+                            let result = #results;
+                            *self.#query_names = core::option::Option::Some(result);
+                        )*
+                    }
+                } else {
+                    quote::quote! {
+                        let content = <serde_query::__priv::Content<'de> as serde_query::__priv::serde::de::Deserialize<'de>>::deserialize(deserializer)?;
+                        #(
+                            let deserializer = serde_query::__priv::ContentDeserializer::<D::Error>::new(content.clone());
+                            let result = #results;
+                            *self.#query_names = core::option::Option::Some(result);
+                        )*
+                    }
+                };
+
                 quote::quote! {
-                    struct #deserialize_seed_ty<'query> {
-                        #query_name: &'query mut core::option::Option<
-                            core::result::Result<
-                                #query_type,
-                                serde_query::__priv::Error,
-                            >
-                        >,
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn deserialize<D>(self, deserializer: D) -> core::result::Result<Self::Value, D::Error>
                         where
                             D: serde_query::__priv::serde::Deserializer<'de>,
                         {
-                            let result = match <#query_type as serde_query::__priv::serde::Deserialize<'de>>::deserialize(deserializer) {
-                                core::result::Result::Ok(v) => core::result::Result::Ok(v),
-                                core::result::Result::Err(e) => core::result::Result::Err(
-                                    serde_query::__priv::Error::owned(#field, #prefix, e.to_string())
-                                ),
-                            };
-                            *self.#query_name = core::option::Option::Some(result);
+                            #body
                             core::result::Result::Ok(())
                         }
                     }
@@ -343,12 +991,11 @@ impl Node {
                     .map(|name| Literal::byte_string(name.as_bytes()))
                     .collect();
 
-                let (missing_field_names, missing_query_names, missing_query_name_strings) =
-                    Self::missing_fields_error_triple(fields);
-                let missing_field_error_messages = missing_field_names
-                    .into_iter()
-                    .map(|field_name| format!("missing field '{}'", field_name));
                 let prefix = &self.prefix;
+                let missing_field_fallback =
+                    Self::missing_children_fallback(fields, prefix, |field_name| {
+                        format!("missing field '{}'", field_name)
+                    });
 
                 let match_arms =
                     fields
@@ -357,7 +1004,7 @@ impl Node {
                         .map(|((field, node), field_id)| {
                             let deserialize_seed_ty = node.deserialize_seed_ty();
                             let query_names = node.query_names();
-                            let query_name_strings = query_names.iter().map(|ident| ident.to_string());
+                            let query_name_strings = node.query_fields();
                             let duplicated_field_message = format!("duplicated field '{}'", field);
 
                             quote::quote! {
@@ -386,7 +1033,8 @@ impl Node {
                                             core::option::Option::None => &mut self.#query_names,
                                         };
                                     )*
-                                    map.next_value_seed(#deserialize_seed_ty {
+                                    map.next_value_seed(#deserialize_seed_ty #turbofish {
+                                        #marker_init
                                         #(
                                             #query_names,
                                         )*
@@ -400,14 +1048,16 @@ impl Node {
                         fields.keys().map(|name| format!("'{name}'")).collect();
                     format!("one of the following fields: {}", field_names.join(", or "))
                 };
+                let deserialize_visitor = self.deserialize_visitor("map_or_enum", &expecting);
+                let visit_enum = Self::generate_visit_enum(fields, prefix, generics);
 
                 let child_code = fields
                     .values()
-                    .map(|node| node.generate())
+                    .map(|node| node.generate(generics))
                     .collect::<Result<Vec<_>, _>>()?;
 
                 quote::quote! {
-                    struct #deserialize_seed_ty<'query> {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::option::Option<
                                 core::result::Result<
@@ -416,40 +1066,30 @@ impl Node {
                                 >
                             >,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
                         where
                             D: serde_query::__priv::serde::Deserializer<'de>,
                         {
-                            let visitor = #visitor_ty {
+                            let visitor = #visitor_ty #turbofish {
+                                #marker_init
                                 #(
                                     #query_names: self.#query_names,
                                 )*
                             };
-                            deserializer.deserialize_map(visitor)?;
-                            #(
-                                if self.#missing_query_names.is_none() {
-                                    *self.#missing_query_names = core::option::Option::Some(
-                                        core::result::Result::Err(
-                                            serde_query::__priv::Error::borrowed(
-                                                #missing_query_name_strings,
-                                                #prefix,
-                                                #missing_field_error_messages,
-                                            )
-                                        )
-                                    );
-                                }
-                            )*
+                            #deserialize_visitor
+                            #(#missing_field_fallback)*
 
                             core::result::Result::Ok(())
                         }
                     }
 
-                    struct #visitor_ty<'query> {
+                    struct #visitor_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::option::Option<
                                 core::result::Result<
@@ -458,9 +1098,10 @@ impl Node {
                                 >
                             >,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
@@ -481,6 +1122,8 @@ impl Node {
                             }
                             core::result::Result::Ok(())
                         }
+
+                        #visit_enum
                     }
 
                     enum #field_deserialize_enum_ty {
@@ -536,50 +1179,33 @@ impl Node {
                     #(#child_code)*
                 }
             }
-            NodeKind::IndexArray { indices } => {
+            NodeKind::Variant { variants } => {
                 let deserialize_seed_ty = self.deserialize_seed_ty();
                 let visitor_ty = self.visitor_ty();
 
                 let query_names = self.query_names();
                 let query_types = self.query_types();
+                let prefix = &self.prefix;
 
-                let match_arms = indices.iter().map(|(index, node)| {
-                    let deserialize_seed_ty = node.deserialize_seed_ty();
-                    let query_names = node.query_names();
+                let expecting = {
+                    let variant_names: Vec<_> =
+                        variants.keys().map(|name| format!("'{name}'")).collect();
+                    format!(
+                        "one of the following variants: {}",
+                        variant_names.join(", or ")
+                    )
+                };
+                let deserialize_visitor = self.deserialize_visitor("enum", &expecting);
 
-                    quote::quote! {
-                        #index => {
-                            match seq.next_element_seed(#deserialize_seed_ty {
-                                #(
-                                    #query_names: self.#query_names,
-                                )*
-                            })? {
-                                core::option::Option::Some(_) => {},
-                                core::option::Option::None => break,
-                            };
-                        }
-                    }
-                });
+                let visit_enum = Self::generate_visit_enum(variants, prefix, generics);
 
-                let (missing_field_names, missing_query_names, missing_query_name_strings) =
-                    Self::missing_fields_error_triple(indices);
-                let missing_field_error_messages = missing_field_names
-                    .into_iter()
-                    .map(|index| format!("the sequence must have at least {} elements", index + 1));
-                let prefix = &self.prefix;
-
-                let (max_index, _) = indices
-                    .last_key_value()
-                    .expect("IndexArray node must have at least one element");
-                let expecting = format!("a sequence with at least {} elements", max_index + 1);
-
-                let child_code = indices
-                    .values()
-                    .map(|node| node.generate())
-                    .collect::<Result<Vec<_>, _>>()?;
+                let child_code = variants
+                    .values()
+                    .map(|node| node.generate(generics))
+                    .collect::<Result<Vec<_>, _>>()?;
 
                 quote::quote! {
-                    struct #deserialize_seed_ty<'query> {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::option::Option<
                                 core::result::Result<
@@ -588,39 +1214,29 @@ impl Node {
                                 >
                             >,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
                         where
                             D: serde_query::__priv::serde::Deserializer<'de>,
                         {
-                            let visitor = #visitor_ty {
+                            let visitor = #visitor_ty #turbofish {
+                                #marker_init
                                 #(
                                     #query_names: self.#query_names,
                                 )*
                             };
-                            deserializer.deserialize_seq(visitor)?;
-                            #(
-                                if self.#missing_query_names.is_none() {
-                                    *self.#missing_query_names = core::option::Option::Some(
-                                        core::result::Result::Err(
-                                            serde_query::__priv::Error::borrowed(
-                                                #missing_query_name_strings,
-                                                #prefix,
-                                                #missing_field_error_messages,
-                                            )
-                                        )
-                                    );
-                                }
-                            )*
+                            #deserialize_visitor
+
                             core::result::Result::Ok(())
                         }
                     }
 
-                    struct #visitor_ty<'query> {
+                    struct #visitor_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::option::Option<
                                 core::result::Result<
@@ -629,19 +1245,60 @@ impl Node {
                                 >
                             >,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
                             core::fmt::Formatter::write_str(formatter, #expecting)
                         }
 
-                        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-                        where
-                            A: serde_query::__priv::serde::de::SeqAccess<'de>,
-                        {
+                        #visit_enum
+                    }
+
+                    #(#child_code)*
+                }
+            }
+            NodeKind::IndexArray { indices } => {
+                let deserialize_seed_ty = self.deserialize_seed_ty();
+                let visitor_ty = self.visitor_ty();
+
+                let query_names = self.query_names();
+                let query_types = self.query_types();
+
+                // Elements at negative indices are only known at the end of the sequence.
+                // If there are any, we buffer the last elements in a window.
+                let window_capacity = indices
+                    .keys()
+                    .filter(|index| **index < 0)
+                    .map(|index| index.unsigned_abs())
+                    .max();
+
+                let visit_seq_body = match window_capacity {
+                    None => {
+                        let match_arms = indices.iter().map(|(index, node)| {
+                            let index = *index as usize;
+                            let deserialize_seed_ty = node.deserialize_seed_ty();
+                            let query_names = node.query_names();
+
+                            quote::quote! {
+                                #index => {
+                                    match seq.next_element_seed(#deserialize_seed_ty #turbofish {
+                                        #marker_init
+                                        #(
+                                            #query_names: self.#query_names,
+                                        )*
+                                    })? {
+                                        core::option::Option::Some(_) => {},
+                                        core::option::Option::None => break,
+                                    };
+                                }
+                            }
+                        });
+
+                        quote::quote! {
                             let mut current_index = 0usize;
                             loop {
                                 match current_index {
@@ -655,6 +1312,215 @@ impl Node {
                                 }
                                 current_index += 1;
                             }
+                        }
+                    }
+                    Some(window_capacity) => {
+                        // If the length is known, negative indices are resolved up front and the
+                        // elements are streamed. Only an element reached by both a non-negative and a
+                        // negative index is buffered.
+                        let children: Vec<_> = indices
+                            .iter()
+                            .enumerate()
+                            .map(|(position, (index, node))| {
+                                let at = quote::format_ident!("at{}", position);
+                                let condition = if *index < 0 {
+                                    let index = index.unsigned_abs();
+                                    quote::quote!(current_index + #index == len)
+                                } else {
+                                    let index = *index as usize;
+                                    quote::quote!(current_index == #index)
+                                };
+                                let deserialize_seed_ty = node.deserialize_seed_ty();
+                                let query_names = node.query_names();
+                                let seed = quote::quote! {
+                                    #deserialize_seed_ty #turbofish {
+                                        #marker_init
+                                        #(
+                                            #query_names: self.#query_names,
+                                        )*
+                                    }
+                                };
+                                (at, condition, seed)
+                            })
+                            .collect();
+                        let ats: Vec<_> = children.iter().map(|(at, _, _)| at).collect();
+                        let conditions = children.iter().map(|(_, condition, _)| condition);
+                        let stream_arms = children.iter().map(|(at, _, seed)| {
+                            quote::quote! {
+                                if #at {
+                                    seq.next_element_seed(#seed)?.is_some()
+                                } else
+                            }
+                        });
+                        let buffered_arms = children.iter().map(|(at, _, seed)| {
+                            quote::quote! {
+                                if #at {
+                                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                        #seed,
+                                        serde_query::__priv::ContentDeserializer::<A::Error>::new(element.clone()),
+                                    )?;
+                                }
+                            }
+                        });
+                        let known_len_body = quote::quote! {
+                            let mut current_index = 0usize;
+                            loop {
+                                #(
+                                    let #ats = #conditions;
+                                )*
+                                let found = if 0usize #(+ #ats as usize)* > 1 {
+                                    match seq.next_element::<serde_query::__priv::Content<'de>>()? {
+                                        core::option::Option::Some(element) => {
+                                            #(#buffered_arms)*
+                                            true
+                                        }
+                                        core::option::Option::None => false,
+                                    }
+                                } else #(#stream_arms)* {
+                                    seq.next_element::<serde_query::__priv::serde::de::IgnoredAny>()?.is_some()
+                                };
+                                if !found {
+                                    break;
+                                }
+                                current_index += 1;
+                            }
+                        };
+
+                        let positive_arms = indices.range(0..).map(|(index, node)| {
+                            let index = *index as usize;
+                            let deserialize_seed_ty = node.deserialize_seed_ty();
+                            let query_names = node.query_names();
+
+                            quote::quote! {
+                                if current_index == #index {
+                                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                        #deserialize_seed_ty #turbofish {
+                                            #marker_init
+                                            #(
+                                                #query_names: self.#query_names,
+                                            )*
+                                        },
+                                        serde_query::__priv::ContentDeserializer::<A::Error>::new(element.clone()),
+                                    )?;
+                                }
+                            }
+                        });
+                        let negative_arms = indices.range(..0).map(|(index, node)| {
+                            let index = index.unsigned_abs();
+                            let deserialize_seed_ty = node.deserialize_seed_ty();
+                            let query_names = node.query_names();
+
+                            quote::quote! {
+                                if let core::option::Option::Some(element) = window.take_from_end(#index) {
+                                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                        #deserialize_seed_ty #turbofish {
+                                            #marker_init
+                                            #(
+                                                #query_names: self.#query_names,
+                                            )*
+                                        },
+                                        serde_query::__priv::ContentDeserializer::<A::Error>::new(element),
+                                    )?;
+                                }
+                            }
+                        });
+
+                        quote::quote! {
+                            match serde_query::__priv::serde::de::SeqAccess::size_hint(&seq) {
+                                core::option::Option::Some(len) => {
+                                    #known_len_body
+                                }
+                                core::option::Option::None => {
+                                    let mut window = serde_query::__priv::Window::new(#window_capacity);
+                                    while let core::option::Option::Some(element) = seq.next_element::<serde_query::__priv::Content<'de>>()? {
+                                        let current_index = window.count();
+                                        #(#positive_arms)*
+                                        window.push(element);
+                                    }
+                                    #(#negative_arms)*
+                                }
+                            }
+                        }
+                    }
+                };
+
+                let missing_index_fallback =
+                    Self::missing_children_fallback(indices, &self.prefix, |index| {
+                        format!(
+                            "the sequence must have at least {} elements",
+                            min_sequence_len(*index)
+                        )
+                    });
+
+                let min_len = indices
+                    .keys()
+                    .map(|index| min_sequence_len(*index))
+                    .max()
+                    .expect("IndexArray node must have at least one element");
+                let expecting = format!("a sequence with at least {} elements", min_len);
+                let deserialize_visitor = self.deserialize_visitor("seq", &expecting);
+
+                let child_code = indices
+                    .values()
+                    .map(|node| node.generate(generics))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                quote::quote! {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
+                        type Value = ();
+
+                        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: serde_query::__priv::serde::Deserializer<'de>,
+                        {
+                            let visitor = #visitor_ty #turbofish {
+                                #marker_init
+                                #(
+                                    #query_names: self.#query_names,
+                                )*
+                            };
+                            #deserialize_visitor
+                            #(#missing_index_fallback)*
+                            core::result::Result::Ok(())
+                        }
+                    }
+
+                    struct #visitor_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty #ty_generics #impl_where {
+                        type Value = ();
+
+                        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                            core::fmt::Formatter::write_str(formatter, #expecting)
+                        }
+
+                        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                        where
+                            A: serde_query::__priv::serde::de::SeqAccess<'de>,
+                        {
+                            #visit_seq_body
                             core::result::Result::Ok(())
                         }
                     }
@@ -663,18 +1529,54 @@ impl Node {
                 }
             }
             NodeKind::CollectArray { child } => {
+                self.generate_collect_array(child, None, generics)?
+            }
+            NodeKind::SliceArray { start, end, child } => {
+                self.generate_collect_array(child, Some((*start, *end)), generics)?
+            }
+            NodeKind::CollectMap { child } => {
                 let deserialize_seed_ty = self.deserialize_seed_ty();
                 let visitor_ty = self.visitor_ty();
 
                 let query_names = self.query_names();
+                let query_name_strings = self.query_fields();
                 let query_types = self.query_types();
+                let prefix = &self.prefix;
 
-                let child_code = child.generate()?;
+                let child_code = child.generate(generics)?;
                 let child_deserialize_seed_ty = child.deserialize_seed_ty();
-                // child_query_names should be equal to those of self
+                let deserialize_visitor = self.deserialize_visitor("map", "a map");
+                // A single query deserializes the key directly. Queries may have different key types,
+                // so otherwise the key is buffered and deserialized for each query.
+                let (next_key, keys) = match query_types.as_slice() {
+                    [ty] => (
+                        quote::quote! {
+                            map.next_key::<<#ty as serde_query::__priv::MapContainer>::Key>()?
+                        },
+                        vec![quote::quote!(core::result::Result::<_, A::Error>::Ok(key))],
+                    ),
+                    _ => (
+                        quote::quote! {
+                            map.next_key::<serde_query::__priv::Content<'de>>()?
+                        },
+                        query_types
+                            .iter()
+                            .map(|ty| {
+                                quote::quote! {
+                                    <
+                                        <#ty as serde_query::__priv::MapContainer>::Key
+                                        as serde_query::__priv::serde::Deserialize<'de>
+                                    >::deserialize(
+                                        serde_query::__priv::MapKeyDeserializer::<A::Error>::new(key.clone()),
+                                    )
+                                }
+                            })
+                            .collect(),
+                    ),
+                };
 
                 quote::quote! {
-                    struct #deserialize_seed_ty<'query> {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::option::Option<
                                 core::result::Result<
@@ -683,9 +1585,10 @@ impl Node {
                                 >
                             >,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
@@ -694,15 +1597,16 @@ impl Node {
                         {
                             #(
                                 let mut #query_names = core::result::Result::Ok(
-                                    <#query_types as serde_query::__priv::Container>::empty()
+                                    <#query_types as serde_query::__priv::MapContainer>::empty()
                                 );
                             )*
-                            let visitor = #visitor_ty {
+                            let visitor = #visitor_ty #turbofish {
+                                #marker_init
                                 #(
                                     #query_names: &mut #query_names,
                                 )*
                             };
-                            deserializer.deserialize_seq(visitor)?;
+                            #deserialize_visitor
                             #(
                                 *self.#query_names = core::option::Option::Some(#query_names);
                             )*
@@ -710,61 +1614,70 @@ impl Node {
                         }
                     }
 
-                    struct #visitor_ty<'query> {
+                    struct #visitor_ty #seed_generics #seed_where {
                         #(
                             #query_names: &'query mut core::result::Result<#query_types, serde_query::__priv::Error>,
                         )*
+                        #marker
                     }
 
-                    impl<'query, 'de> serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty<'query> {
+                    impl #impl_generics serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty #ty_generics #impl_where {
                         type Value = ();
 
                         fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                            core::fmt::Formatter::write_str(formatter, "a sequence")
+                            core::fmt::Formatter::write_str(formatter, "a map")
                         }
 
-                        fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+                        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
                         where
-                            A: serde_query::__priv::serde::de::SeqAccess<'de>,
+                            A: serde_query::__priv::serde::de::MapAccess<'de>,
                         {
-                            if let core::option::Option::Some(additional) = seq.size_hint() {
+                            if let core::option::Option::Some(additional) = map.size_hint() {
                                 #(
-                                    <#query_types as serde_query::__priv::Container>::reserve(
+                                    <#query_types as serde_query::__priv::MapContainer>::reserve(
                                         self.#query_names.as_mut().unwrap(),
                                         additional,
                                     );
                                 )*
                             }
-                            loop {
+                            while let core::option::Option::Some(key) = #next_key {
                                 #(
                                     let mut #query_names = core::option::Option::None;
                                 )*
-                                match seq.next_element_seed(#child_deserialize_seed_ty {
+                                map.next_value_seed(#child_deserialize_seed_ty #turbofish {
+                                    #marker_init
                                     #(
                                         #query_names: &mut #query_names,
                                     )*
-                                })? {
-                                    core::option::Option::None => break,
-                                    core::option::Option::Some(()) => {
-                                        #(
-                                            match &mut self.#query_names {
-                                                core::result::Result::Ok(ref mut container) => match #query_names {
-                                                    core::option::Option::Some(core::result::Result::Ok(v)) => {
-                                                        <#query_types as serde_query::__priv::Container>::extend_one(
+                                })?;
+                                #(
+                                    match &mut self.#query_names {
+                                        core::result::Result::Ok(ref mut container) => match #query_names {
+                                            core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                                match #keys {
+                                                    core::result::Result::Ok(k) => {
+                                                        <#query_types as serde_query::__priv::MapContainer>::insert(
                                                             container,
+                                                            k,
                                                             v,
                                                         )
-                                                    },
-                                                    core::option::Option::Some(core::result::Result::Err(e)) => {
-                                                        *self.#query_names = core::result::Result::Err(e);
-                                                    },
-                                                    core::option::Option::None => unreachable!(),
-                                                },
-                                                core::result::Result::Err(_) => {},
-                                            }
-                                        )*
+                                                    }
+                                                    core::result::Result::Err(e) => {
+                                                        *self.#query_names = core::result::Result::Err(
+                                                            serde_query::__priv::Error::owned(#query_name_strings, #prefix, e.to_string())
+                                                        );
+                                                    }
+                                                }
+                                            },
+                                            core::option::Option::Some(core::result::Result::Err(e)) => {
+                                                *self.#query_names = core::result::Result::Err(e);
+                                            },
+                                            // filtered out by select(...)
+                                            core::option::Option::None => {},
+                                        },
+                                        core::result::Result::Err(_) => {},
                                     }
-                                };
+                                )*
                             }
                             core::result::Result::Ok(())
                         }
@@ -773,6 +1686,226 @@ impl Node {
                     #child_code
                 }
             }
+            NodeKind::RecursiveDescent { name, child } => {
+                let deserialize_seed_ty = self.deserialize_seed_ty();
+                let visitor_ty = self.visitor_ty();
+
+                let query_names = self.query_names();
+                let query_types = self.query_types();
+
+                let child_code = child.generate(generics)?;
+                let child_deserialize_seed_ty = child.deserialize_seed_ty();
+                let extend_containers = self.extend_containers();
+
+                quote::quote! {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
+                        type Value = ();
+
+                        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: serde_query::__priv::serde::Deserializer<'de>,
+                        {
+                            #(
+                                let mut #query_names = core::result::Result::Ok(
+                                    <#query_types as serde_query::__priv::Container>::empty()
+                                );
+                            )*
+                            let mut visitor = #visitor_ty #turbofish {
+                                #marker_init
+                                #(
+                                    #query_names: &mut #query_names,
+                                )*
+                            };
+                            serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                serde_query::__priv::DescendSeed(&mut visitor),
+                                deserializer,
+                            )?;
+                            #(
+                                *self.#query_names = core::option::Option::Some(#query_names);
+                            )*
+                            core::result::Result::Ok(())
+                        }
+                    }
+
+                    struct #visitor_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::result::Result<#query_types, serde_query::__priv::Error>,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::Descend<'de> for #visitor_ty #ty_generics #impl_where {
+                        const KEY: &'static str = #name;
+
+                        fn accept<D>(&mut self, deserializer: D) -> Result<(), D::Error>
+                        where
+                            D: serde_query::__priv::serde::Deserializer<'de>,
+                        {
+                            #(
+                                let mut #query_names = core::option::Option::None;
+                            )*
+                            serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                #child_deserialize_seed_ty #turbofish {
+                                    #marker_init
+                                    #(
+                                        #query_names: &mut #query_names,
+                                    )*
+                                },
+                                deserializer,
+                            )?;
+                            #extend_containers
+                            core::result::Result::Ok(())
+                        }
+                    }
+
+                    #child_code
+                }
+            }
+            NodeKind::Select { branches } => {
+                let deserialize_seed_ty = self.deserialize_seed_ty();
+
+                let query_names = self.query_names();
+                let query_types = self.query_types();
+
+                let mut child_codes = vec![];
+                let mut filters = vec![];
+                for (predicate, child) in branches.iter() {
+                    child_codes.push(child.generate(generics)?);
+                    let child_deserialize_seed_ty = child.deserialize_seed_ty();
+                    let child_query_names = child.query_names();
+                    let predicate = generate_predicate(predicate);
+                    filters.push(quote::quote! {
+                        if serde_query::__priv::select(&content, #predicate) {
+                            serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                #child_deserialize_seed_ty #turbofish {
+                                    #marker_init
+                                    #(
+                                        #child_query_names: self.#child_query_names,
+                                    )*
+                                },
+                                serde_query::__priv::ContentDeserializer::<D::Error>::new(content.clone()),
+                            )?;
+                        }
+                    });
+                }
+
+                quote::quote! {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
+                        type Value = ();
+
+                        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: serde_query::__priv::serde::Deserializer<'de>,
+                        {
+                            // Buffer the value because the fields in the predicate may come after the queried fields.
+                            // The slots of the filtered-out queries are left empty.
+                            let content = <serde_query::__priv::Content<'de> as serde_query::__priv::serde::Deserialize<'de>>::deserialize(deserializer)?;
+                            #(#filters)*
+                            core::result::Result::Ok(())
+                        }
+                    }
+
+                    #(#child_codes)*
+                }
+            }
+            NodeKind::Function { functions } => {
+                let deserialize_seed_ty = self.deserialize_seed_ty();
+
+                let query_names = self.query_names();
+                let query_types = self.query_types();
+                let prefix = &self.prefix;
+
+                let keys = functions.contains_key(&Function::Keys);
+                let mut has = vec![];
+                let results = functions
+                    .iter()
+                    .flat_map(|(function, ids)| {
+                        let index = has.len();
+                        if let Function::Has(key) = function {
+                            has.push(key);
+                        }
+                        ids.iter().map(move |id| {
+                            let query_name = id.ident();
+                            let query_type = &self.queries[id];
+                            let field = id.field();
+                            let result = match function {
+                                Function::Length => {
+                                    quote::quote!(builtins.length::<#query_type>(#field, #prefix))
+                                }
+                                Function::Keys => {
+                                    quote::quote!(builtins.keys::<#query_type>(#field, #prefix))
+                                }
+                                Function::Has(_) => quote::quote!(
+                                    builtins.has::<#query_type>(#index, #field, #prefix)
+                                ),
+                                Function::Type => quote::quote!(
+                                    builtins.type_name::<#query_type>(#field, #prefix)
+                                ),
+                            };
+                            quote::quote! {
+                                *self.#query_name = core::option::Option::Some(#result);
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                quote::quote! {
+                    struct #deserialize_seed_ty #seed_generics #seed_where {
+                        #(
+                            #query_names: &'query mut core::option::Option<
+                                core::result::Result<
+                                    #query_types,
+                                    serde_query::__priv::Error,
+                                >
+                            >,
+                        )*
+                        #marker
+                    }
+
+                    impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
+                        type Value = ();
+
+                        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                        where
+                            D: serde_query::__priv::serde::Deserializer<'de>,
+                        {
+                            let builtins = serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                                serde_query::__priv::BuiltinSeed {
+                                    keys: #keys,
+                                    has: &[#(#has),*],
+                                },
+                                deserializer,
+                            )?;
+                            #(#results)*
+                            core::result::Result::Ok(())
+                        }
+                    }
+                }
+            }
             NodeKind::None => {
                 // No queries. Generate an empty DeserializeSeed for the root node.
                 let deserialize_seed_ty = self.deserialize_seed_ty();
@@ -795,54 +1928,416 @@ impl Node {
         })
     }
 
+    /// Generates code that pushes the results of the child queries for an element into the containers.
+    ///
+    /// The results must be stored in the local variables named after the queries.
+    /// The first error replaces the container.
+    fn extend_containers(&self) -> TokenStream {
+        let query_names = self.query_names();
+        let query_types = self.query_types();
+
+        quote::quote! {
+            #(
+                match &mut self.#query_names {
+                    core::result::Result::Ok(ref mut container) => match #query_names {
+                        core::option::Option::Some(core::result::Result::Ok(v)) => {
+                            <#query_types as serde_query::__priv::Container>::extend_one(
+                                container,
+                                v,
+                            )
+                        },
+                        core::option::Option::Some(core::result::Result::Err(e)) => {
+                            *self.#query_names = core::result::Result::Err(e);
+                        },
+                        // filtered out by select(...)
+                        core::option::Option::None => {},
+                    },
+                    core::result::Result::Err(_) => {},
+                }
+            )*
+        }
+    }
+
+    /// Generates code for `.[]` and `.[start:end]`.
+    ///
+    /// If `range` is given, only the elements in the range are fed into the containers.
+    /// Otherwise, the values of a map are fed into the containers as well.
+    fn generate_collect_array(
+        &self,
+        child: &Node,
+        range: Option<(usize, Option<usize>)>,
+        generics: &Generics,
+    ) -> Result<TokenStream, Diagnostic> {
+        let seed_generics = generics.decl();
+        let seed_where = generics.decl_where();
+        let impl_generics = generics.impl_generics();
+        let ty_generics = generics.ty_generics();
+        let impl_where = generics.impl_where();
+        let marker = generics.marker_field();
+        let marker_init = generics.marker_init();
+        let turbofish = generics.turbofish();
+        let deserialize_seed_ty = self.deserialize_seed_ty();
+        let visitor_ty = self.visitor_ty();
+
+        let query_names = self.query_names();
+        let query_types = self.query_types();
+
+        let child_code = child.generate(generics)?;
+        let child_deserialize_seed_ty = child.deserialize_seed_ty();
+        // child_query_names should be equal to those of self
+
+        let extend_containers = self.extend_containers();
+        let (expecting, deserialize_visitor, visit_map) = match range {
+            Some(_) => (
+                "a sequence",
+                self.deserialize_visitor("seq", "a sequence"),
+                quote::quote!(),
+            ),
+            None => (
+                "a sequence or a map",
+                self.deserialize_visitor("seq_or_map", "a sequence or a map"),
+                quote::quote! {
+                    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: serde_query::__priv::serde::de::MapAccess<'de>,
+                    {
+                        if let core::option::Option::Some(additional) = map.size_hint() {
+                            #(
+                                <#query_types as serde_query::__priv::Container>::reserve(
+                                    self.#query_names.as_mut().unwrap(),
+                                    additional,
+                                );
+                            )*
+                        }
+                        while map.next_key::<serde_query::__priv::serde::de::IgnoredAny>()?.is_some() {
+                            #(
+                                let mut #query_names = core::option::Option::None;
+                            )*
+                            map.next_value_seed(#child_deserialize_seed_ty #turbofish {
+                                #marker_init
+                                #(
+                                    #query_names: &mut #query_names,
+                                )*
+                            })?;
+                            #extend_containers
+                        }
+                        core::result::Result::Ok(())
+                    }
+                },
+            ),
+        };
+
+        // Elements outside of the slice are skipped without being fed into the container.
+        let skip_condition = match range {
+            None | Some((0, None)) => None,
+            Some((start, None)) => Some(quote::quote!(current_index < #start)),
+            Some((start, Some(end))) => {
+                Some(quote::quote!(!(#start..#end).contains(&current_index)))
+            }
+        };
+        let (index_init, index_skip) = match &skip_condition {
+            None => (quote::quote!(), quote::quote!()),
+            Some(skip_condition) => (
+                quote::quote! {
+                    let mut current_index = 0usize;
+                },
+                quote::quote! {
+                    let skip = #skip_condition;
+                    current_index += 1;
+                    if skip {
+                        match seq.next_element::<serde_query::__priv::serde::de::IgnoredAny>()? {
+                            core::option::Option::Some(_) => continue,
+                            core::option::Option::None => break,
+                        }
+                    }
+                },
+            ),
+        };
+        let reserve_additional = match range {
+            None => quote::quote!(additional),
+            Some((start, None)) => quote::quote!(additional.saturating_sub(#start)),
+            Some((start, Some(end))) => quote::quote! {
+                core::cmp::min(additional.saturating_sub(#start), #end.saturating_sub(#start))
+            },
+        };
+
+        Ok(quote::quote! {
+            struct #deserialize_seed_ty #seed_generics #seed_where {
+                #(
+                    #query_names: &'query mut core::option::Option<
+                        core::result::Result<
+                            #query_types,
+                            serde_query::__priv::Error,
+                        >
+                    >,
+                )*
+                #marker
+            }
+
+            impl #impl_generics serde_query::__priv::serde::de::DeserializeSeed<'de> for #deserialize_seed_ty #ty_generics #impl_where {
+                type Value = ();
+
+                fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+                where
+                    D: serde_query::__priv::serde::Deserializer<'de>,
+                {
+                    #(
+                        let mut #query_names = core::result::Result::Ok(
+                            <#query_types as serde_query::__priv::Container>::empty()
+                        );
+                    )*
+                    let visitor = #visitor_ty #turbofish {
+                        #marker_init
+                        #(
+                            #query_names: &mut #query_names,
+                        )*
+                    };
+                    #deserialize_visitor
+                    #(
+                        *self.#query_names = core::option::Option::Some(#query_names);
+                    )*
+                    core::result::Result::Ok(())
+                }
+            }
+
+            struct #visitor_ty #seed_generics #seed_where {
+                #(
+                    #query_names: &'query mut core::result::Result<#query_types, serde_query::__priv::Error>,
+                )*
+                #marker
+            }
+
+            impl #impl_generics serde_query::__priv::serde::de::Visitor<'de> for #visitor_ty #ty_generics #impl_where {
+                type Value = ();
+
+                fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                    core::fmt::Formatter::write_str(formatter, #expecting)
+                }
+
+                fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: serde_query::__priv::serde::de::SeqAccess<'de>,
+                {
+                    if let core::option::Option::Some(additional) = seq.size_hint() {
+                        #(
+                            <#query_types as serde_query::__priv::Container>::reserve(
+                                self.#query_names.as_mut().unwrap(),
+                                #reserve_additional,
+                            );
+                        )*
+                    }
+                    #index_init
+                    loop {
+                        #index_skip
+                        #(
+                            let mut #query_names = core::option::Option::None;
+                        )*
+                        match seq.next_element_seed(#child_deserialize_seed_ty #turbofish {
+                            #marker_init
+                            #(
+                                #query_names: &mut #query_names,
+                            )*
+                        })? {
+                            core::option::Option::None => break,
+                            core::option::Option::Some(()) => {
+                                #extend_containers
+                            }
+                        };
+                    }
+                    core::result::Result::Ok(())
+                }
+
+                #visit_map
+            }
+
+            #child_code
+        })
+    }
+
     pub(crate) fn generate_deserialize<F: FnOnce(TokenStream) -> TokenStream>(
         &self,
-        struct_ty: &syn::Ident,
-        implementor_ty: &syn::Ident,
+        fields: &[FieldQueries],
+        struct_ty: TokenStream,
+        implementor_ty: TokenStream,
+        generics: &Generics,
         construction: F,
     ) -> TokenStream {
         let deserialize_seed_ty = self.deserialize_seed_ty();
+        let de_impl_generics = generics.de_impl_generics();
+        let impl_where = generics.impl_where();
+        // The seed of a struct without queries is not generic.
+        let (root_ty_generics, turbofish, marker_init) = match self.kind {
+            NodeKind::None => (quote::quote!(), quote::quote!(), quote::quote!()),
+            _ => (
+                generics.elided_ty_generics(),
+                generics.turbofish(),
+                generics.marker_init(),
+            ),
+        };
         let query_names = self.query_names();
         let construction = construction(quote::quote!(value));
+
+        // The slot of a query is empty only if the value is missing and the field has a default.
+        let query_results = self.queries.keys().map(|id| {
+            let query_name = id.ident();
+            let default = fields.iter().find_map(|field| match &field.default {
+                Some(default) if field.queries.last() == Some(id) => Some(default),
+                _ => None,
+            });
+            match default {
+                Some(default) => quote::quote! {
+                    let #query_name = #query_name.unwrap_or_else(|| core::result::Result::Ok(#default));
+                },
+                None => quote::quote! {
+                    let #query_name = #query_name.unwrap();
+                },
+            }
+        });
+
+        // Report errors in the order of the field names.
+        let mut fields: Vec<&FieldQueries> = fields.iter().collect();
+        fields.sort_by(|f1, f2| match (&f1.member, &f2.member) {
+            (syn::Member::Unnamed(i1), syn::Member::Unnamed(i2)) => i1.index.cmp(&i2.index),
+            _ => f1.ident.cmp(&f2.ident),
+        });
+
+        let field_members: Vec<&syn::Member> = fields.iter().map(|field| &field.member).collect();
+        let field_names: Vec<&syn::Ident> = fields.iter().map(|field| &field.ident).collect();
+        let mut field_results = vec![];
+        let mut has_errors = vec![];
+        let mut errors = vec![];
+        for field in fields.iter() {
+            let field_name = &field.ident;
+            if field.queries.len() == 1 && field.fallback.is_none() {
+                // The slot of the only query is named after the field.
+                has_errors.push(quote::quote!(#field_name.is_err()));
+                errors.push(quote::quote!(#field_name.err()));
+                continue;
+            }
+
+            // The field takes the first successful alternative.
+            // The errors are reported only if every alternative fails.
+            let slots: Vec<&syn::Ident> = field.queries.iter().map(QueryId::ident).collect();
+            let mut error_names = slots
+                .iter()
+                .map(|slot| quote::quote!(#slot))
+                .collect::<Vec<_>>();
+            let fallback = field.fallback.as_ref().map(|literal| {
+                let fallback_name = quote::format_ident!("__{}_fallback", field_name.unraw());
+                let field_name_string = field.queries[0].field();
+                let literal_string = literal.to_string();
+                let content = match literal {
+                    query::Literal::Null => quote::quote!(None),
+                    query::Literal::Bool(b) => quote::quote!(Bool(#b)),
+                    query::Literal::Number(n) => {
+                        if let Ok(n) = n.parse::<u64>() {
+                            quote::quote!(U64(#n))
+                        } else if let Ok(n) = n.parse::<i64>() {
+                            quote::quote!(I64(#n))
+                        } else {
+                            let n = Literal::f64_unsuffixed(n.parse().unwrap());
+                            quote::quote!(F64(#n))
+                        }
+                    }
+                    query::Literal::String(s) => quote::quote!(Str(#s)),
+                };
+                error_names.push(quote::quote!(#fallback_name));
+                quote::quote! {
+                    let #fallback_name = if #field_name.is_none() {
+                        match serde_query::__priv::literal(
+                            #field_name_string,
+                            #literal_string,
+                            serde_query::__priv::Content::#content,
+                        ) {
+                            core::result::Result::Ok(v) => {
+                                #field_name = core::option::Option::Some(v);
+                                core::option::Option::None
+                            }
+                            core::result::Result::Err(e) => core::option::Option::Some(e),
+                        }
+                    } else {
+                        core::option::Option::None
+                    };
+                }
+            });
+            field_results.push(quote::quote! {
+                let mut #field_name = core::option::Option::None;
+                #(
+                    let #slots = match #slots {
+                        core::result::Result::Ok(v) => {
+                            if #field_name.is_none() {
+                                #field_name = core::option::Option::Some(v);
+                            }
+                            core::option::Option::None
+                        }
+                        core::result::Result::Err(e) => core::option::Option::Some(e),
+                    };
+                )*
+                #fallback
+            });
+            has_errors.push(quote::quote!(#field_name.is_none()));
+            errors.extend(error_names.into_iter().map(|error_name| {
+                quote::quote! {
+                    if #field_name.is_none() {
+                        #error_name
+                    } else {
+                        core::option::Option::None
+                    }
+                }
+            }));
+        }
+
         quote::quote! {
-            impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for #implementor_ty {
-                fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+            impl #de_impl_generics serde_query::__priv::DeserializeNested<'de> for #implementor_ty #impl_where {
+                fn deserialize_nested<D>(deserializer: D) -> core::result::Result<core::result::Result<Self, serde_query::__priv::Errors>, D::Error>
                 where
                     D: serde_query::__priv::serde::de::Deserializer<'de>
                 {
                     #(
                         let mut #query_names = core::option::Option::None;
                     )*
-                    let root = #deserialize_seed_ty {
+                    let root = #deserialize_seed_ty #turbofish {
+                        #marker_init
                         #(
                             #query_names: &mut #query_names,
                         )*
                     };
-                    <#deserialize_seed_ty as serde_query::__priv::serde::de::DeserializeSeed<'de>>::deserialize(root, deserializer)?;
+                    <#deserialize_seed_ty #root_ty_generics as serde_query::__priv::serde::de::DeserializeSeed<'de>>::deserialize(root, deserializer)?;
 
-                    #(
-                        let #query_names = #query_names.unwrap();
-                    )*
+                    #(#query_results)*
+                    #(#field_results)*
                     let has_error = false #(
-                        || #query_names.is_err()
+                        || #has_errors
                     )*;
 
                     if !has_error {
                         let value = #struct_ty {
                             #(
-                                #query_names: #query_names.unwrap(),
+                                #field_members: #field_names.unwrap(),
                             )*
                         };
-                        core::result::Result::Ok(#construction)
+                        core::result::Result::Ok(core::result::Result::Ok(#construction))
                     } else {
                         let errors = [
                             #(
-                                #query_names.err(),
+                                #errors,
                             )*
                         ];
-                        core::result::Result::Err(
-                            <D::Error as serde_query::__priv::serde::de::Error>::custom(serde_query::__priv::Errors::new(&errors))
-                        )
+                        core::result::Result::Ok(core::result::Result::Err(serde_query::__priv::Errors::new(errors)))
+                    }
+                }
+            }
+
+            impl #de_impl_generics serde_query::__priv::serde::de::Deserialize<'de> for #implementor_ty #impl_where {
+                fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+                where
+                    D: serde_query::__priv::serde::de::Deserializer<'de>
+                {
+                    match <Self as serde_query::__priv::DeserializeNested<'de>>::deserialize_nested(deserializer)? {
+                        core::result::Result::Ok(value) => core::result::Result::Ok(value),
+                        core::result::Result::Err(errors) => core::result::Result::Err(
+                            <D::Error as serde_query::__priv::serde::de::Error>::custom(errors)
+                        ),
                     }
                 }
             }
@@ -1,95 +1,731 @@
 use proc_macro_error::{diagnostic, Diagnostic, Level};
 use quote::ToTokens;
-use syn::{DeriveInput, LitStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use proc_macro2::{Span, TokenStream};
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, DeriveInput, LitStr, Token,
+};
 
 use crate::{
-    parse_query,
-    query::{Query, QueryId},
+    case::RenameRule,
+    parse_jsonpath, parse_pointer,
+    parse_query::{self, Alternatives, ParseError},
+    query::{DeserializeWith, FieldQueries, PathSegment, Query, QueryFragment, QueryId},
 };
 
-pub(crate) struct ParseResult {
+/// The syntax of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Jq,
+    Pointer,
+    JsonPath,
+}
+
+impl Syntax {
+    /// Guesses the syntax from the prefix: `/` for JSON Pointer and `$` for JSONPath.
+    fn detect(query: &str) -> Self {
+        if query.starts_with('/') {
+            Syntax::Pointer
+        } else if query.starts_with('$') {
+            Syntax::JsonPath
+        } else {
+            Syntax::Jq
+        }
+    }
+
+    fn parse(self, query: &str) -> (Alternatives, Vec<ParseError>) {
+        match self {
+            Syntax::Jq => parse_query::parse(query),
+            Syntax::Pointer => parse_pointer::parse(query),
+            Syntax::JsonPath => parse_jsonpath::parse(query),
+        }
+    }
+}
+
+/// The arguments of `#[query("...", syntax = "...", default, deserialize_with = "...")]`.
+struct QueryArgs {
+    query: LitStr,
+    syntax: Option<Syntax>,
+    /// The expression for the default value of the field.
+    default: Option<TokenStream>,
+    /// `deserialize_with`, `with`, or `nested`, and how it deserializes the value.
+    deserialize_with: Option<(syn::Ident, DeserializeWith)>,
+}
+
+impl Parse for QueryArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(LitStr) {
+            return Err(input.error("#[query(...)] takes a string literal"));
+        }
+        let query = input.parse()?;
+        let mut syntax = None;
+        let mut default = None;
+        let mut deserialize_with = None;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: syn::Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "syntax" => {
+                    if syntax.is_some() {
+                        return Err(syn::Error::new(key.span(), "duplicated `syntax`"));
+                    }
+                    input.parse::<Token![=]>()?;
+                    let value: LitStr = input.parse()?;
+                    syntax = Some(match value.value().as_str() {
+                        "jq" => Syntax::Jq,
+                        "pointer" => Syntax::Pointer,
+                        "jsonpath" => Syntax::JsonPath,
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                r#"expected "jq", "pointer", or "jsonpath""#,
+                            ))
+                        }
+                    });
+                }
+                "default" => {
+                    if default.is_some() {
+                        return Err(syn::Error::new(key.span(), "duplicated `default`"));
+                    }
+                    default = Some(if input.peek(Token![=]) {
+                        input.parse::<Token![=]>()?;
+                        let value: LitStr = input.parse()?;
+                        let path: syn::ExprPath = value.parse()?;
+                        quote::quote_spanned!(value.span()=> #path())
+                    } else {
+                        quote::quote_spanned!(key.span()=> core::default::Default::default())
+                    });
+                }
+                // `with = "module"` is a shorthand for `deserialize_with = "module::deserialize"`.
+                "deserialize_with" | "with" | "nested" => {
+                    if deserialize_with.is_some() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "duplicated `deserialize_with`, `with`, or `nested`",
+                        ));
+                    }
+                    let function = if key == "nested" {
+                        DeserializeWith::Nested
+                    } else {
+                        input.parse::<Token![=]>()?;
+                        let value: LitStr = input.parse()?;
+                        let path: syn::ExprPath = value.parse()?;
+                        DeserializeWith::Function(if key == "with" {
+                            quote::quote_spanned!(value.span()=> #path::deserialize)
+                        } else {
+                            path.to_token_stream()
+                        })
+                    };
+                    deserialize_with = Some((key, function));
+                }
+                _ => return Err(syn::Error::new(
+                    key.span(),
+                    "unknown argument, expected `syntax`, `default`, `deserialize_with`, `with`, or `nested`",
+                )),
+            }
+        }
+        Ok(Self {
+            query,
+            syntax,
+            default,
+            deserialize_with,
+        })
+    }
+}
+
+/// The queries for the fields of a struct or an enum variant.
+#[derive(Default)]
+pub(crate) struct ParsedFields {
+    pub(crate) fields: Vec<FieldQueries>,
     pub(crate) queries: Vec<Query>,
+}
+
+pub(crate) struct ParsedVariant {
+    pub(crate) ident: syn::Ident,
+    /// The value of `#[query(tag_value = "...")]`.
+    pub(crate) tag_value: Option<String>,
+    pub(crate) fields: ParsedFields,
+}
+
+pub(crate) enum ParsedData {
+    Struct(ParsedFields),
+    Enum {
+        /// The path of `#[query(tag = "...")]`.
+        /// Without a tag, the variants are tried in the declaration order.
+        tag: Option<Vec<PathSegment>>,
+        variants: Vec<ParsedVariant>,
+    },
+}
+
+/// An argument of `#[query(...)]` on a struct, an enum, or a variant, e.g. `implicit` or `tag = ".type"`.
+struct ItemArg {
+    key: syn::Ident,
+    value: Option<LitStr>,
+}
+
+impl Parse for ItemArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.call(syn::Ident::parse_any)?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { key, value })
+    }
+}
+
+/// Parses the arguments of `#[query(...)]` in `attrs`.
+///
+/// Every argument in `keys` takes a string literal except `implicit`.
+fn parse_item_args(
+    attrs: &[Attribute],
+    keys: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> BTreeMap<String, (Attribute, Option<LitStr>)> {
+    let mut ret = BTreeMap::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("query")) {
+        let args = match attr.parse_args_with(Punctuated::<ItemArg, Token![,]>::parse_terminated) {
+            Ok(args) => args,
+            Err(error) => {
+                diagnostics.push(diagnostic!(error.span(), Level::Error, "{}", error));
+                continue;
+            }
+        };
+        for arg in args {
+            let key = arg.key.to_string();
+            if !keys.contains(&key.as_str()) {
+                let expected: Vec<String> = keys.iter().map(|key| format!("`{}`", key)).collect();
+                diagnostics.push(diagnostic!(
+                    arg.key,
+                    Level::Error,
+                    "unknown argument, expected {}",
+                    expected.join(" or ")
+                ));
+                continue;
+            }
+            match (key.as_str(), &arg.value) {
+                ("implicit", Some(value)) => {
+                    diagnostics.push(diagnostic!(
+                        value,
+                        Level::Error,
+                        "`implicit` does not take a value"
+                    ));
+                    continue;
+                }
+                ("implicit", None) | (_, Some(_)) => {}
+                (_, None) => {
+                    diagnostics.push(diagnostic!(
+                        arg.key,
+                        Level::Error,
+                        r#"`{}` takes a string literal, e.g. `{} = "..."`"#,
+                        key,
+                        key
+                    ));
+                    continue;
+                }
+            }
+            if ret.contains_key(&key) {
+                diagnostics.push(diagnostic!(arg.key, Level::Error, "duplicated `{}`", key));
+                continue;
+            }
+            ret.insert(key, (attr.clone(), arg.value));
+        }
+    }
+    ret
+}
+
+/// Returns the case convention of the implicit queries, or `None` without `#[query(implicit)]`.
+fn parse_implicit(
+    args: &BTreeMap<String, (Attribute, Option<LitStr>)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<RenameRule> {
+    let rule = match args.get("rename_all") {
+        Some((_, Some(lit))) => match RenameRule::parse(&lit.value()) {
+            Some(rule) => rule,
+            None => {
+                let expected: Vec<String> = RenameRule::NAMES
+                    .iter()
+                    .map(|name| format!("{:?}", name))
+                    .collect();
+                diagnostics.push(diagnostic!(
+                    lit,
+                    Level::Error,
+                    "unknown case convention, expected one of {}",
+                    expected.join(", ")
+                ));
+                RenameRule::None
+            }
+        },
+        _ => RenameRule::None,
+    };
+    match (args.get("implicit"), args.get("rename_all")) {
+        (Some(_), _) => Some(rule),
+        (None, Some((attr, _))) => {
+            diagnostics.push(diagnostic!(
+                attr,
+                Level::Error,
+                "`rename_all` requires `implicit`"
+            ));
+            None
+        }
+        (None, None) => None,
+    }
+}
+
+/// Parses `#[query(root = "...")]`. The queries start at the document root without it.
+fn parse_root(
+    args: &BTreeMap<String, (Attribute, Option<LitStr>)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<PathSegment> {
+    match args.get("root") {
+        Some((attr, Some(lit))) => parse_path(
+            attr,
+            lit,
+            false,
+            "the root must be a path of fields and indices, e.g. `.data.attributes`",
+            diagnostics,
+        )
+        .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+/// Parses the path of `#[query(tag = "...")]` or `#[query(root = "...")]`, which consists of fields and indices.
+///
+/// Reports `message` if the query is not such a path, or if it does not start with a field when `first_field` holds.
+fn parse_path(
+    attr: &Attribute,
+    lit: &LitStr,
+    first_field: bool,
+    message: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Vec<PathSegment>> {
+    let argument = lit.value();
+    let (alternatives, errors) = Syntax::detect(&argument).parse(&argument);
+    if !errors.is_empty() {
+        for error in errors {
+            diagnostics.push(error_diagnostic(attr, lit, error));
+        }
+        return None;
+    }
+
+    let mut path = vec![];
+    let mut fragment = match (alternatives.queries.len(), alternatives.fallback) {
+        (1, None) => alternatives.queries.into_iter().next(),
+        _ => None,
+    };
+    loop {
+        fragment = match fragment {
+            Some(QueryFragment::Accept) => break,
+            Some(QueryFragment::Field { name, quoted, rest }) => {
+                path.push(PathSegment::Field { name, quoted });
+                Some(*rest)
+            }
+            Some(QueryFragment::IndexArray { index, rest })
+                if index >= 0 && !(first_field && path.is_empty()) =>
+            {
+                path.push(PathSegment::Index(index as usize));
+                Some(*rest)
+            }
+            _ => None,
+        };
+        if fragment.is_none() {
+            break;
+        }
+    }
+    if fragment.is_none() || (first_field && path.is_empty()) {
+        diagnostics.push(diagnostic!(lit, Level::Error, "{}", message));
+        return None;
+    }
+    Some(path)
+}
+
+pub(crate) struct ParseResult {
+    pub(crate) data: ParsedData,
     pub(crate) diagnostics: Vec<Diagnostic>,
 }
 
-pub(crate) fn parse_input(input: &mut DeriveInput) -> ParseResult {
-    let mut diagnostics = vec![];
-    let queries = match &mut input.data {
-        syn::Data::Struct(data) => data
-            .fields
-            .iter_mut()
-            .flat_map(|field| {
-                let mut attr_pos = None;
-                for (pos, attr) in field.attrs.iter().enumerate() {
-                    if attr.path.is_ident("query") {
-                        if attr_pos.is_some() {
+/// Maps a byte range of the query to the characters in the string literal.
+///
+/// Returns `None` if the compiler does not support sub-spans, or if the literal contains escape sequences.
+fn subspan(lit: &LitStr, range: &Range<usize>) -> Option<Span> {
+    let source = lit.token().to_string();
+    let value = lit.value();
+    // skip `"` or `r#"`
+    let offset = source.find('"')? + 1;
+    if source.get(offset..offset + value.len())? != value {
+        return None;
+    }
+    // point at the closing quote for errors at the end of the query
+    let end = range.end.max(range.start + 1);
+    lit.token().subspan(offset + range.start..offset + end)
+}
+
+/// Reports a parse error at the characters in the query, or at the attribute with the byte range as a fallback.
+fn error_diagnostic(attr: &Attribute, lit: &LitStr, error: ParseError) -> Diagnostic {
+    let diagnostic = match subspan(lit, &error.span) {
+        Some(span) => Diagnostic::spanned(span, Level::Error, error.message.clone()),
+        None => diagnostic!(attr, Level::Error, error.to_string()),
+    };
+    match error.help {
+        Some(help) => diagnostic.help(help),
+        None => diagnostic,
+    }
+}
+
+/// Returns whether `ty` is `Cow<'a, str>` or `Cow<'a, [u8]>` with one of `lifetimes`, optionally
+/// inside `Option` or `Vec` (e.g., `Vec<Cow<'a, str>>`).
+///
+/// The queries for such a field borrow `Cow` from the input, as `#[serde(borrow)]` does. Other
+/// types containing `Cow` use their `Deserialize` implementations, which do not borrow.
+fn borrows_cow(ty: &syn::Type, lifetimes: &[syn::Ident]) -> bool {
+    let segment = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => segment,
+            None => return false,
+        },
+        syn::Type::Group(group) => return borrows_cow(&group.elem, lifetimes),
+        syn::Type::Paren(paren) => return borrows_cow(&paren.elem, lifetimes),
+        _ => return false,
+    };
+    let args: Vec<&syn::GenericArgument> = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().collect(),
+        _ => return false,
+    };
+    match (segment.ident.to_string().as_str(), args.as_slice()) {
+        ("Option" | "Vec", [syn::GenericArgument::Type(inner)]) => borrows_cow(inner, lifetimes),
+        ("Cow", [syn::GenericArgument::Lifetime(lifetime), syn::GenericArgument::Type(inner)]) => {
+            lifetimes.contains(&lifetime.ident)
+                && match inner {
+                    syn::Type::Path(path) => path.qself.is_none() && path.path.is_ident("str"),
+                    syn::Type::Slice(slice) => matches!(
+                        &*slice.elem,
+                        syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("u8")
+                    ),
+                    _ => false,
+                }
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether the query ends with a built-in function, which does not deserialize the value.
+fn ends_with_function(fragment: &QueryFragment) -> bool {
+    match fragment {
+        QueryFragment::Accept => false,
+        QueryFragment::Function { .. } => true,
+        QueryFragment::Field { rest, .. }
+        | QueryFragment::IndexArray { rest, .. }
+        | QueryFragment::CollectArray { rest }
+        | QueryFragment::CollectMap { rest }
+        | QueryFragment::SliceArray { rest, .. }
+        | QueryFragment::RecursiveField { rest, .. }
+        | QueryFragment::Variant { rest, .. }
+        | QueryFragment::Select { rest, .. }
+        | QueryFragment::Optional { rest }
+        | QueryFragment::OptionalCollection { rest } => ends_with_function(rest),
+    }
+}
+
+/// Parses the queries of the fields.
+///
+/// The queries start at `root`.
+/// With `implicit`, an unannotated field named `field_name` is queried with `.field_name` renamed by the rule.
+fn parse_fields(
+    fields: &mut syn::Fields,
+    lifetimes: &[syn::Ident],
+    root: &[PathSegment],
+    implicit: Option<RenameRule>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> ParsedFields {
+    let mut queries = vec![];
+    let fields = fields
+        .iter_mut()
+        .enumerate()
+        .flat_map(|(position, field)| {
+            let mut attr_pos = None;
+            for (pos, attr) in field.attrs.iter().enumerate() {
+                if attr.path.is_ident("query") {
+                    if attr_pos.is_some() {
+                        diagnostics.push(diagnostic!(
+                            attr,
+                            Level::Error,
+                            "duplicated #[query(...)]"
+                        ));
+                    }
+                    attr_pos = Some(pos);
+                }
+            }
+
+            // Tuple fields are referred to by their positions, e.g. `0`.
+            let (ident, member, name) = match &field.ident {
+                Some(ident) => (
+                    ident.clone(),
+                    syn::Member::Named(ident.clone()),
+                    ident.to_string(),
+                ),
+                None => {
+                    let span = field.ty.span();
+                    (
+                        quote::format_ident!("__field{}", position, span = span),
+                        syn::Member::Unnamed(syn::Index {
+                            index: position as u32,
+                            span,
+                        }),
+                        position.to_string(),
+                    )
+                }
+            };
+
+            let (alternatives, default, deserialize_with) = match (attr_pos, implicit, &field.ident)
+            {
+                (Some(pos), _, _) => {
+                    let attr = field.attrs.remove(pos);
+                    let args = match attr.parse_args::<QueryArgs>() {
+                        Err(error) => {
+                            diagnostics.push(diagnostic!(error.span(), Level::Error, "{}", error));
+                            return None;
+                        }
+                        Ok(args) => args,
+                    };
+                    let lit = &args.query;
+                    let argument = lit.value();
+                    let syntax = args.syntax.unwrap_or_else(|| Syntax::detect(&argument));
+
+                    let (alternatives, errors) = syntax.parse(&argument);
+                    for error in errors {
+                        diagnostics.push(error_diagnostic(&attr, lit, error));
+                    }
+                    if args.default.is_some() && alternatives.fallback.is_some() {
+                        diagnostics.push(diagnostic!(
+                            lit,
+                            Level::Error,
+                            "`default` cannot be used with a literal fallback"
+                        ));
+                    }
+                    if let Some((key, _)) = &args.deserialize_with {
+                        if alternatives.fallback.is_some() {
                             diagnostics.push(diagnostic!(
-                                attr,
+                                lit,
                                 Level::Error,
-                                "duplicated #[query(...)]"
+                                "`{}` cannot be used with a literal fallback",
+                                key
+                            ));
+                        }
+                        if alternatives.queries.iter().any(ends_with_function) {
+                            diagnostics.push(diagnostic!(
+                                lit,
+                                Level::Error,
+                                "`{}` cannot be used with built-in functions",
+                                key
                             ));
                         }
-                        attr_pos = Some(pos);
                     }
+                    let deserialize_with = args.deserialize_with.map(|(_, function)| function);
+                    (alternatives, args.default, deserialize_with)
+                }
+                (None, Some(rule), Some(field_ident)) => {
+                    let field_name = rule.apply(&field_ident.unraw().to_string());
+                    let alternatives = Alternatives {
+                        queries: vec![parse_query::lower(vec![parse_query::Query::field(
+                            field_name,
+                        )])],
+                        fallback: None,
+                    };
+                    (alternatives, None, None)
+                }
+                _ => {
+                    diagnostics.push(diagnostic!(field, Level::Error, "no #[query(...)]"));
+                    return None;
                 }
+            };
 
-                match attr_pos {
-                    None => {
-                        diagnostics.push(diagnostic!(field, Level::Error, "no #[query(...)]"));
-                        None
-                    }
-                    Some(pos) => {
-                        let attr = field.attrs.remove(pos);
-                        let argument = match attr.parse_args::<LitStr>() {
-                            Err(_) => {
-                                diagnostics.push(diagnostic!(
-                                    field,
-                                    Level::Error,
-                                    "#[query(...)] takes a string literal"
-                                ));
-                                return None;
-                            }
-                            Ok(lit) => lit.value(),
-                        };
-                        let ident = match &field.ident {
-                            None => {
+            let ty = field.ty.to_token_stream();
+            let deserialize_with = match deserialize_with {
+                Some(deserialize_with) => deserialize_with,
+                None if borrows_cow(&field.ty, lifetimes) => DeserializeWith::Borrow,
+                None => DeserializeWith::Deserialize,
+            };
+            let single = alternatives.queries.len() == 1 && alternatives.fallback.is_none();
+            let last = alternatives.queries.len().saturating_sub(1);
+            let mut ids = vec![];
+            for (index, fragment) in alternatives.queries.into_iter().enumerate() {
+                // Each alternative needs its own slot
+                let slot = if single {
+                    ident.clone()
+                } else {
+                    quote::format_ident!(
+                        "__{}_alternative{}",
+                        ident.unraw(),
+                        index,
+                        span = ident.span()
+                    )
+                };
+                let id = QueryId::new(slot, name.clone());
+                ids.push(id.clone());
+                let fragment = root
+                    .iter()
+                    .rev()
+                    .fold(fragment, |rest, segment| match segment {
+                        PathSegment::Field { name, quoted } => {
+                            QueryFragment::field(name.clone(), *quoted, rest)
+                        }
+                        PathSegment::Index(index) => {
+                            QueryFragment::index_array(*index as isize, rest)
+                        }
+                    });
+                // The default takes over when the last alternative is missing as well.
+                let default = default.is_some() && index == last;
+                queries.push(Query::new(
+                    id,
+                    fragment,
+                    ty.clone(),
+                    deserialize_with.clone(),
+                    default,
+                ));
+            }
+
+            Some(FieldQueries {
+                ident,
+                member,
+                ty: field.ty.clone(),
+                deserialize_with,
+                queries: ids,
+                fallback: alternatives.fallback,
+                default,
+            })
+        })
+        .collect();
+
+    ParsedFields { fields, queries }
+}
+
+pub(crate) fn parse_input(input: &mut DeriveInput) -> ParseResult {
+    let mut diagnostics = vec![];
+    let lifetimes: Vec<syn::Ident> = input
+        .generics
+        .lifetimes()
+        .map(|param| param.lifetime.ident.clone())
+        .collect();
+    let data = match &mut input.data {
+        syn::Data::Struct(data) => {
+            let args = parse_item_args(
+                &input.attrs,
+                &["root", "implicit", "rename_all"],
+                &mut diagnostics,
+            );
+            let implicit = parse_implicit(&args, &mut diagnostics);
+            let root = parse_root(&args, &mut diagnostics);
+            ParsedData::Struct(parse_fields(
+                &mut data.fields,
+                &lifetimes,
+                &root,
+                implicit,
+                &mut diagnostics,
+            ))
+        }
+        syn::Data::Enum(data) => {
+            if data.variants.is_empty() {
+                diagnostics.push(diagnostic!(
+                    input.ident,
+                    Level::Error,
+                    "serde-query does not support enums without variants"
+                ));
+            }
+            let mut args = parse_item_args(
+                &input.attrs,
+                &["tag", "root", "implicit", "rename_all"],
+                &mut diagnostics,
+            );
+            let implicit = parse_implicit(&args, &mut diagnostics);
+            let root = parse_root(&args, &mut diagnostics);
+            let tag_attr = args.remove("tag");
+            let tag = tag_attr.as_ref().and_then(|(attr, lit)| {
+                parse_path(
+                    attr,
+                    lit.as_ref().unwrap(),
+                    true,
+                    "the tag must be a path of fields and indices starting with a field, e.g. `.type`",
+                    &mut diagnostics,
+                )
+            });
+            let mut tag_values = BTreeSet::new();
+            let variants = data
+                .variants
+                .iter_mut()
+                .map(|variant| {
+                    let tag_value = parse_item_args(&variant.attrs, &["tag_value"], &mut diagnostics)
+                        .remove("tag_value")
+                        .map(|(attr, lit)| (attr, lit.unwrap()));
+                    let tag_value = match (&tag_attr, tag_value) {
+                        (Some(_), Some((_, lit))) => {
+                            if !tag_values.insert(lit.value()) {
                                 diagnostics.push(diagnostic!(
-                                    field.ident,
+                                    lit,
                                     Level::Error,
-                                    "#[query(...)] field must be named",
+                                    "duplicated tag value '{}'",
+                                    lit.value()
                                 ));
-                                return None;
                             }
-                            Some(ident) => ident.clone(),
-                        };
-
-                        let (fragment, errors) = parse_query::parse(&argument);
-                        for error in errors {
-                            diagnostics.push(diagnostic!(attr, Level::Error, error.message));
+                            Some(lit.value())
+                        }
+                        (Some(_), None) => {
+                            diagnostics.push(diagnostic!(
+                                variant.ident,
+                                Level::Error,
+                                r#"no #[query(tag_value = "...")]"#
+                            ));
+                            None
                         }
-                        Some(Query::new(
-                            QueryId::new(ident),
-                            fragment,
-                            field.ty.to_token_stream(),
-                        ))
+                        (None, Some((attr, _))) => {
+                            diagnostics.push(diagnostic!(
+                                attr,
+                                Level::Error,
+                                r#"#[query(tag_value = "...")] requires #[query(tag = "...")] on the enum"#
+                            ));
+                            None
+                        }
+                        (None, None) => None,
+                    };
+                    ParsedVariant {
+                        ident: variant.ident.clone(),
+                        tag_value,
+                        fields: parse_fields(
+                            &mut variant.fields,
+                            &lifetimes,
+                            &root,
+                            implicit,
+                            &mut diagnostics,
+                        ),
                     }
-                }
-            })
-            .collect(),
-        _ => {
+                })
+                .collect();
+            ParsedData::Enum { tag, variants }
+        }
+        syn::Data::Union(_) => {
             diagnostics.push(diagnostic!(
                 input,
                 Level::Error,
-                "serde-query supports only structs"
+                "serde-query supports only structs and enums"
             ));
-            vec![]
+            ParsedData::Struct(ParsedFields::default())
         }
     };
 
-    ParseResult {
-        queries,
-        diagnostics,
-    }
+    ParseResult { data, diagnostics }
 }
 
 #[cfg(test)]
@@ -134,10 +770,13 @@ struct Foo {
 "#
         );
         snapshot!(
-            to_snapshot_string(&result.queries),
+            to_snapshot_string(match &result.data {
+                ParsedData::Struct(fields) => &fields.queries,
+                ParsedData::Enum { .. } => unreachable!(),
+            }),
             r#"
-Query { id: QueryId(Ident { sym: with_query, span: bytes(36..46) }), fragment: Accept, ty: TokenStream [Ident { sym: i64, span: bytes(48..51) }] }
-Query { id: QueryId(Ident { sym: with_multiple_queries, span: bytes(95..116) }), fragment: Field { name: "y", quoted: false, rest: Accept }, ty: TokenStream [Ident { sym: i32, span: bytes(118..121) }] }
+Query { id: QueryId { ident: Ident { sym: with_query, span: bytes(36..46) }, field: "with_query" }, fragment: Accept, ty: TokenStream [Ident { sym: i64, span: bytes(48..51) }], deserialize_with: Deserialize, default: false }
+Query { id: QueryId { ident: Ident { sym: with_multiple_queries, span: bytes(95..116) }, field: "with_multiple_queries" }, fragment: Field { name: "y", quoted: false, rest: Accept }, ty: TokenStream [Ident { sym: i32, span: bytes(118..121) }], deserialize_with: Deserialize, default: false }
 "#
         );
         snapshot!(
@@ -145,6 +784,35 @@ Query { id: QueryId(Ident { sym: with_multiple_queries, span: bytes(95..116) }),
             r#"
 Diagnostic { level: Error, span_range: SpanRange { first: bytes(76..77), last: bytes(77..90) }, msg: "duplicated #[query(...)]", suggestions: [], children: [] }
 Diagnostic { level: Error, span_range: SpanRange { first: bytes(127..135), last: bytes(137..143) }, msg: "no #[query(...)]", suggestions: [], children: [] }
+"#
+        );
+    }
+
+    #[test]
+    fn snapshot_test_parse_error() {
+        let mut input: DeriveInput = syn::parse_str(
+            r##"
+struct Foo {
+    #[query(".items.0")]
+    index: i64,
+    #[query(r#".[kind]"#)]
+    raw: String,
+    #[query(".[\"\\x\"].0")]
+    escaped: String,
+}
+"##,
+        )
+        .unwrap();
+
+        let result = parse_input(&mut input);
+
+        snapshot!(
+            to_snapshot_string(&result.diagnostics),
+            r#"
+Diagnostic { level: Error, span_range: SpanRange { first: bytes(35..36), last: bytes(35..36) }, msg: "expected '[', '{}', an identifier, or a quoted key, got an index `0`", suggestions: [(Help, "did you mean `.[0]`?", None)], children: [] }
+Diagnostic { level: Error, span_range: SpanRange { first: bytes(72..78), last: bytes(72..78) }, msg: "expected an index, a slice, or a quoted field inside indexing, got an identifier `kind`", suggestions: [(Help, "did you mean `[\\"kind\\"]`?", None)], children: [] }
+Diagnostic { level: Error, span_range: SpanRange { first: bytes(104..105), last: bytes(105..128) }, msg: "3..5: invalid escape sequence '\\\\x'", suggestions: [], children: [] }
+Diagnostic { level: Error, span_range: SpanRange { first: bytes(104..105), last: bytes(105..128) }, msg: "8..9: expected '[', '{}', an identifier, or a quoted key, got an index `0`", suggestions: [(Help, "did you mean `.[0]`?", None)], children: [] }
 "#
         );
     }
@@ -0,0 +1,306 @@
+//! JSONPath syntax.
+//!
+//! Only the paths that can be expressed with the jq syntax are supported:
+//! `$`, `.name`, `['name']`, `[n]`, `[start:end]`, `.*`, `[*]`, and `..name`.
+
+use crate::parse_query::{lower, unescape, Alternatives, ParseError, Query};
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&mut self, start: usize, end: usize, message: impl std::fmt::Display) {
+        self.errors
+            .push(ParseError::new(start..end, message.to_string()));
+    }
+
+    /// Reads a member name such as `items` or `名前`.
+    fn name(&mut self) -> Option<String> {
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return None;
+        }
+        let name = self.rest()[..len].to_string();
+        self.pos += len;
+        Some(name)
+    }
+
+    /// Parses the inside of `[...]`. `start` is the position of '['.
+    fn bracket(&mut self, start: usize) -> Option<Query> {
+        // find the matching ']' outside of quotes
+        let mut quote = None;
+        let mut escape = false;
+        let mut close = None;
+        for (offset, c) in self.rest().char_indices() {
+            match (quote, c) {
+                (Some(_), _) if escape => escape = false,
+                (Some(_), '\\') => escape = true,
+                (Some(q), c) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, ']') => {
+                    close = Some(offset);
+                    break;
+                }
+                (None, _) => {}
+            }
+        }
+        let close = match close {
+            Some(close) => close,
+            None => {
+                self.error(start, self.input.len(), "expected an ']', got EOF");
+                self.pos = self.input.len();
+                return None;
+            }
+        };
+        let raw = &self.rest()[..close];
+        let inner_start = self.pos + (raw.len() - raw.trim_start().len());
+        let inner = raw.trim();
+        self.pos += close + 1;
+        let end = self.pos;
+
+        let query = match inner.as_bytes() {
+            [b'*'] => Query::CollectArray,
+            [b'?', ..] => {
+                self.error(
+                    start,
+                    end,
+                    "filter expressions are not supported, use '| select(...)' in the jq syntax instead",
+                );
+                return None;
+            }
+            [q @ (b'\'' | b'"'), .., last] if q == last && inner.len() >= 2 => {
+                match unescape(&inner[1..inner.len() - 1], *q as char) {
+                    Ok(name) => Query::field(name),
+                    Err(error) => {
+                        // skip the opening quote
+                        let content_start = inner_start + 1;
+                        self.error(
+                            content_start + error.start,
+                            content_start + error.end,
+                            error.message,
+                        );
+                        return None;
+                    }
+                }
+            }
+            _ if inner.contains(',') => {
+                self.error(start, end, "unions are not supported");
+                return None;
+            }
+            _ if inner.contains(':') => {
+                let mut bounds = inner.split(':');
+                let (lower, upper) = (bounds.next().unwrap(), bounds.next().unwrap());
+                if bounds.next().is_some() {
+                    self.error(start, end, "slices with a step are not supported");
+                    return None;
+                }
+                let lower = match lower.trim() {
+                    "" => Some(0),
+                    lower => lower.parse().ok(),
+                };
+                let upper = match upper.trim() {
+                    "" => Some(None),
+                    upper => upper.parse().ok().map(Some),
+                };
+                match (lower, upper) {
+                    (Some(start), Some(end)) => Query::Slice { start, end },
+                    _ => {
+                        self.error(
+                            start,
+                            end,
+                            "expected non-negative integers as the bounds of a slice",
+                        );
+                        return None;
+                    }
+                }
+            }
+            _ => match inner.parse() {
+                Ok(index) => Query::Index(index),
+                Err(_) => {
+                    self.error(
+                        start,
+                        end,
+                        "expected an index, a slice, '*', or a quoted name inside brackets",
+                    );
+                    return None;
+                }
+            },
+        };
+        Some(query)
+    }
+}
+
+/// Parses a JSONPath such as `$.data.items[0].id`.
+pub(crate) fn parse(input: &str) -> (Alternatives, Vec<ParseError>) {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        errors: vec![],
+    };
+    let mut queries = vec![];
+
+    if parser.peek() == Some('$') {
+        parser.pos += 1;
+    } else {
+        let end = parser.peek().map_or(0, char::len_utf8);
+        parser.error(0, end, "a JSONPath must start with '$'");
+        parser.pos = input.len();
+    }
+
+    while let Some(c) = parser.peek() {
+        let start = parser.pos;
+        if parser.rest().starts_with("..") {
+            parser.pos += 2;
+            match parser.name() {
+                Some(name) => queries.push(Query::RecursiveField { name }),
+                None => {
+                    parser.error(start, parser.pos, "expected a name after '..'");
+                    break;
+                }
+            }
+        } else if c == '.' {
+            parser.pos += 1;
+            if parser.peek() == Some('*') {
+                parser.pos += 1;
+                queries.push(Query::CollectArray);
+            } else {
+                match parser.name() {
+                    Some(name) => queries.push(Query::field(name)),
+                    None => {
+                        parser.error(start, parser.pos, "expected a name or '*' after '.'");
+                        break;
+                    }
+                }
+            }
+        } else if c == '[' {
+            parser.pos += 1;
+            if let Some(query) = parser.bracket(start) {
+                queries.push(query);
+            }
+        } else {
+            parser.error(
+                start,
+                start + c.len_utf8(),
+                format!("expected '.', '..', or '[', got '{}'", c),
+            );
+            break;
+        }
+    }
+
+    let alternatives = Alternatives {
+        queries: vec![lower(queries)],
+        fallback: None,
+    };
+    (alternatives, parser.errors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::query::QueryFragment;
+
+    fn parse(input: &str) -> (QueryFragment, Vec<ParseError>) {
+        let (mut alternatives, errors) = super::parse(input);
+        (alternatives.queries.remove(0), errors)
+    }
+
+    #[test]
+    fn parser() {
+        let (query, errors) = parse("$.data.items[0].id");
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "data".into(),
+                false,
+                QueryFragment::field(
+                    "items".into(),
+                    false,
+                    QueryFragment::index_array(
+                        0,
+                        QueryFragment::field("id".into(), false, QueryFragment::accept())
+                    )
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#"$['first-name'][*][1:].*["a]b"][-1]..version"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "first-name".into(),
+                true,
+                QueryFragment::collect_array(QueryFragment::slice_array(
+                    1,
+                    None,
+                    QueryFragment::collect_array(QueryFragment::field(
+                        "a]b".into(),
+                        true,
+                        QueryFragment::index_array(
+                            -1,
+                            QueryFragment::recursive_field(
+                                "version".into(),
+                                QueryFragment::accept()
+                            )
+                        )
+                    ))
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse("$");
+        assert_eq!(query, QueryFragment::accept());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn parser_error() {
+        let (_, errors) = parse(".data");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "0..1: a JSONPath must start with '$'"
+        );
+
+        let (_, errors) = parse("$.items[?(@.id == 1)].name");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .to_string()
+            .starts_with("7..21: filter expressions"));
+
+        let (_, errors) = parse(r"$[ 'it\'s' ]['\q']");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            r"14..16: invalid escape sequence '\q'"
+        );
+
+        let (_, errors) = parse("$.items[0,1]");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("7..12: "));
+
+        let (_, errors) = parse("$.items[0");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("7..9: "));
+
+        let (_, errors) = parse("$.items x");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("7..8: "));
+    }
+}
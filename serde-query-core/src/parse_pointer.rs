@@ -0,0 +1,157 @@
+//! JSON Pointer (RFC 6901) syntax.
+
+use crate::parse_query::{lower, Alternatives, ParseError, Query};
+
+/// Parses a JSON pointer such as `/data/items/0/id`.
+///
+/// A segment that is an array index (`0` or a number without leading zeros) extracts an element.
+/// The other segments extract a field.
+pub(crate) fn parse(input: &str) -> (Alternatives, Vec<ParseError>) {
+    let mut errors = vec![];
+    let mut queries = vec![];
+
+    if !input.is_empty() && !input.starts_with('/') {
+        errors.push(ParseError::new(
+            0..input.len(),
+            "a JSON pointer must start with '/'",
+        ));
+    } else {
+        // the position of the '/' before the segment
+        let mut start = 0;
+        for segment in input.split('/').skip(1) {
+            let segment_start = start + 1;
+            let segment_end = segment_start + segment.len();
+            start = segment_end;
+
+            if is_index(segment) {
+                match segment.parse() {
+                    Ok(index) => queries.push(Query::Index(index)),
+                    Err(_) => errors.push(ParseError::new(
+                        segment_start..segment_end,
+                        "the index is too large",
+                    )),
+                }
+                continue;
+            }
+            if segment == "-" {
+                errors.push(ParseError::new(
+                    segment_start..segment_end,
+                    "'-' refers to the element after the end of an array",
+                ));
+                continue;
+            }
+            match unescape(segment) {
+                Ok(name) => queries.push(Query::field(name)),
+                Err(offset) => errors.push(ParseError::new(
+                    segment_start + offset..(segment_start + offset + 2).min(segment_end),
+                    "'~' must be followed by '0' or '1'",
+                )),
+            }
+        }
+    }
+
+    let alternatives = Alternatives {
+        queries: vec![lower(queries)],
+        fallback: None,
+    };
+    (alternatives, errors)
+}
+
+fn is_index(segment: &str) -> bool {
+    match segment.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
+
+/// Replaces `~1` with `/` and `~0` with `~`. Returns the offset of an invalid escape on error.
+fn unescape(segment: &str) -> Result<String, usize> {
+    let mut ret = String::with_capacity(segment.len());
+    let mut chars = segment.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some((_, '0')) => ret.push('~'),
+                Some((_, '1')) => ret.push('/'),
+                _ => return Err(offset),
+            }
+        } else {
+            ret.push(c);
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::query::QueryFragment;
+
+    fn parse(input: &str) -> (QueryFragment, Vec<ParseError>) {
+        let (mut alternatives, errors) = super::parse(input);
+        (alternatives.queries.remove(0), errors)
+    }
+
+    #[test]
+    fn parser() {
+        let (query, errors) = parse("/data/items/0/id");
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "data".into(),
+                false,
+                QueryFragment::field(
+                    "items".into(),
+                    false,
+                    QueryFragment::index_array(
+                        0,
+                        QueryFragment::field("id".into(), false, QueryFragment::accept())
+                    )
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse("/a~1b/m~0n//01");
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "a/b".into(),
+                true,
+                QueryFragment::field(
+                    "m~n".into(),
+                    true,
+                    QueryFragment::field(
+                        "".into(),
+                        true,
+                        QueryFragment::field("01".into(), true, QueryFragment::accept())
+                    )
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse("");
+        assert_eq!(query, QueryFragment::accept());
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn parser_error() {
+        let (_, errors) = parse("data");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "0..4: a JSON pointer must start with '/'"
+        );
+
+        let (_, errors) = parse("/items/-");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("7..8: "));
+
+        let (_, errors) = parse("/a~2b");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().starts_with("2..4: "));
+    }
+}
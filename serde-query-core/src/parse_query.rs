@@ -1,178 +1,927 @@
+use std::{fmt, ops::Range};
+
 use logos::Logos;
 
-use crate::query::QueryFragment;
+use crate::query::{Comparison, Function, Literal, PathSegment, Predicate, QueryFragment};
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum Query {
     Field { name: String, quoted: bool },
-    Index(usize),
+    Index(isize),
     CollectArray,
+    CollectMap,
+    Slice { start: usize, end: Option<usize> },
+    RecursiveField { name: String },
+    Variant { name: String },
+    Select(Predicate),
+    Function(Function),
+    Optional,
+    OptionalCollection,
+}
+
+impl Query {
+    /// A field that is quoted only if its name is not an identifier.
+    pub(crate) fn field(name: String) -> Self {
+        let mut chars = name.chars();
+        let identifier = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        Query::Field {
+            name,
+            quoted: !identifier,
+        }
+    }
 }
 
 #[derive(Logos, Debug, Clone, Copy, PartialEq, Eq)]
 enum Token {
     #[token(r#"."#)]
     Dot,
+    #[token(r#".."#)]
+    DotDot,
     #[token(r#"["#)]
     OpenBracket,
     #[token(r#"]"#)]
     CloseBracket,
+    #[token(r#"{"#)]
+    OpenBrace,
+    #[token(r#"}"#)]
+    CloseBrace,
+    #[token(r#"?"#)]
+    Question,
+    #[token(r#"!"#)]
+    Bang,
+    #[token(r#":"#)]
+    Colon,
+    #[token(r#"|"#)]
+    Pipe,
+    #[token(r#"//"#)]
+    DoubleSlash,
+    #[token(r#"("#)]
+    OpenParen,
+    #[token(r#")"#)]
+    CloseParen,
+    #[token(r#"=="#)]
+    Eq,
+    #[token(r#"!="#)]
+    Ne,
+    #[token(r#"<"#)]
+    Lt,
+    #[token(r#"<="#)]
+    Le,
+    #[token(r#">"#)]
+    Gt,
+    #[token(r#">="#)]
+    Ge,
     #[regex(r#"[a-zA-Z_][0-9a-zA-Z_]*"#)]
     Field,
     // https://github.com/maciejhirsz/logos/issues/133#issuecomment-619444615
-    #[regex(r#""(?:[^"]|\\")*""#)]
+    #[regex(r#""(?:[^"\\]|\\.)*""#)]
     QuotedField,
     #[regex(r#"[0-9]+"#)]
     Index,
+    #[regex(r#"-[0-9]+"#)]
+    NegativeIndex,
+    #[regex(r#"-?[0-9]+\.[0-9]+"#)]
+    Float,
 
     #[error]
     #[regex(r"[ \t\n\f]+", logos::skip)]
     Error,
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Token::Dot => "'.'",
+            Token::DotDot => "'..'",
+            Token::OpenBracket => "'['",
+            Token::CloseBracket => "']'",
+            Token::OpenBrace => "'{'",
+            Token::CloseBrace => "'}'",
+            Token::Question => "'?'",
+            Token::Bang => "'!'",
+            Token::Colon => "':'",
+            Token::Pipe => "'|'",
+            Token::DoubleSlash => "'//'",
+            Token::OpenParen => "'('",
+            Token::CloseParen => "')'",
+            Token::Eq => "'=='",
+            Token::Ne => "'!='",
+            Token::Lt => "'<'",
+            Token::Le => "'<='",
+            Token::Gt => "'>'",
+            Token::Ge => "'>='",
+            Token::Field => "an identifier",
+            Token::QuotedField => "a quoted field",
+            Token::Index => "an index",
+            Token::NegativeIndex => "a negative index",
+            Token::Float => "a number",
+            Token::Error => "an unexpected character",
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseError {
+    /// The byte range of the error in the query.
+    pub span: Range<usize>,
     pub message: String,
+    /// A suggestion to fix the error.
+    pub help: Option<String>,
+}
+
+impl ParseError {
+    pub(crate) fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+            help: None,
+        }
+    }
+
+    pub(crate) fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+/// Describes a token for error messages, e.g. "an identifier `name`".
+fn describe(token: Token, slice: &str) -> String {
+    match token {
+        Token::Field
+        | Token::QuotedField
+        | Token::Index
+        | Token::NegativeIndex
+        | Token::Float
+        | Token::Error => format!("{} `{}`", token, slice),
+        _ => token.to_string(),
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..{}: {}",
+            self.span.start, self.span.end, self.message
+        )
+    }
+}
+
+/// A malformed escape sequence at `start..end` of the contents of a quoted string.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct EscapeError {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) message: String,
+}
+
+/// Decodes the JSON escape sequences in the contents of a quoted string.
+///
+/// `quote` can be escaped as well, so that `\'` is accepted in single-quoted strings.
+pub(crate) fn unescape(content: &str, quote: char) -> Result<String, EscapeError> {
+    let mut ret = String::with_capacity(content.len());
+    let mut chars = content.char_indices().peekable();
+
+    // reads 4 hex digits after '\u' at `start`
+    let hex4 = |chars: &mut std::iter::Peekable<std::str::CharIndices>, start: usize| {
+        let mut code = 0;
+        for _ in 0..4 {
+            match chars.peek() {
+                Some(&(_, c)) if c.is_ascii_hexdigit() => {
+                    code = code * 16 + c.to_digit(16).unwrap();
+                    chars.next();
+                }
+                _ => {
+                    let end = chars.peek().map_or(content.len(), |&(offset, _)| offset);
+                    return Err(EscapeError {
+                        start,
+                        end,
+                        message: "expected 4 hex digits after '\\u'".into(),
+                    });
+                }
+            }
+        }
+        Ok(code)
+    };
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            ret.push(c);
+            continue;
+        }
+        let decoded = match chars.next() {
+            Some((_, '"')) => '"',
+            Some((_, '\\')) => '\\',
+            Some((_, '/')) => '/',
+            Some((_, 'b')) => '\u{8}',
+            Some((_, 'f')) => '\u{c}',
+            Some((_, 'n')) => '\n',
+            Some((_, 'r')) => '\r',
+            Some((_, 't')) => '\t',
+            Some((_, c)) if c == quote => c,
+            Some((_, 'u')) => {
+                let high = hex4(&mut chars, start)?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    // a high surrogate must be followed by a low surrogate
+                    let mut lookahead = chars.clone();
+                    let low = match (lookahead.next(), lookahead.next()) {
+                        (Some((_, '\\')), Some((_, 'u'))) => hex4(&mut lookahead, start).ok(),
+                        _ => None,
+                    };
+                    match low {
+                        Some(low) if (0xDC00..0xE000).contains(&low) => {
+                            chars = lookahead;
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        }
+                        _ => {
+                            return Err(EscapeError {
+                                start,
+                                end: start + 6,
+                                message: "a high surrogate must be followed by a low surrogate"
+                                    .into(),
+                            })
+                        }
+                    }
+                } else if (0xDC00..0xE000).contains(&high) {
+                    return Err(EscapeError {
+                        start,
+                        end: start + 6,
+                        message: "a low surrogate must follow a high surrogate".into(),
+                    });
+                } else {
+                    high
+                };
+                // surrogates are excluded above
+                char::from_u32(code).unwrap()
+            }
+            Some((offset, c)) => {
+                return Err(EscapeError {
+                    start,
+                    end: offset + c.len_utf8(),
+                    message: format!("invalid escape sequence '\\{}'", c),
+                })
+            }
+            None => {
+                return Err(EscapeError {
+                    start,
+                    end: content.len(),
+                    message: "expected an escape sequence after '\\'".into(),
+                })
+            }
+        };
+        ret.push(decoded);
+    }
+
+    Ok(ret)
 }
 
-// very simple handling of escape sequences:
-// \<c> is treated as <c>
+/// Decodes the contents of a quoted field.
+///
+/// Malformed escape sequences are reported by `check_escapes` before parsing, so they are kept as is here.
 fn from_quoted(quoted: &str) -> String {
-    let mut ret = String::with_capacity(quoted.len());
-    let mut escape = false;
+    unescape(quoted, '"').unwrap_or_else(|_| quoted.to_string())
+}
 
-    for c in quoted.chars() {
-        if c == '\\' && !escape {
-            escape = true;
+/// Reports the malformed escape sequences in the quoted fields of `input`.
+fn check_escapes(input: &str, errors: &mut Vec<ParseError>) {
+    for (token, span) in Token::lexer(input).spanned() {
+        if token != Token::QuotedField {
             continue;
         }
-        escape = false;
-        ret.push(c);
+        // skip the opening quote
+        let start = span.start + 1;
+        if let Err(error) = unescape(&input[start..span.end - 1], '"') {
+            errors.push(ParseError::new(
+                start + error.start..start + error.end,
+                error.message,
+            ));
+        }
     }
+}
 
-    ret
+fn parse_literal(token: Token, slice: &str) -> Option<Literal> {
+    match (token, slice) {
+        (Token::QuotedField, s) => Some(Literal::String(from_quoted(&s[1..s.len() - 1]))),
+        (Token::Index | Token::NegativeIndex | Token::Float, n) => {
+            Some(Literal::Number(n.to_string()))
+        }
+        (Token::Field, "null") => Some(Literal::Null),
+        (Token::Field, "true") => Some(Literal::Bool(true)),
+        (Token::Field, "false") => Some(Literal::Bool(false)),
+        _ => None,
+    }
 }
 
-pub(crate) fn parse(input: &str) -> (QueryFragment, Vec<ParseError>) {
+/// Parses the tokens between the parentheses of `select(...)`.
+fn parse_predicate(inner: &[(Token, &str)]) -> Result<Predicate, String> {
+    let mut inner = inner.iter().peekable();
+    match inner.next() {
+        Some((Token::Dot, _)) => {}
+        Some((token, slice)) => {
+            return Err(format!(
+                "expected a path starting with '.', got {}",
+                describe(*token, slice)
+            ))
+        }
+        None => return Err("expected a path starting with '.', got ')'".into()),
+    }
+
+    let mut path = vec![];
+    // whether the path ends with '.'
+    let mut after_dot = true;
+    loop {
+        match (inner.peek(), after_dot) {
+            (Some((Token::Field, name)), true) => {
+                path.push(PathSegment::Field {
+                    name: name.to_string(),
+                    quoted: false,
+                });
+            }
+            (Some((Token::QuotedField, name)), true) => {
+                path.push(PathSegment::Field {
+                    name: from_quoted(&name[1..name.len() - 1]),
+                    quoted: true,
+                });
+            }
+            // brackets may follow either '.' or the previous segment
+            (Some((Token::OpenBracket, _)), _) => {
+                inner.next();
+                match (inner.next(), inner.peek()) {
+                    (Some((Token::Index, index)), Some((Token::CloseBracket, _))) => {
+                        match index.parse() {
+                            Ok(index) => path.push(PathSegment::Index(index)),
+                            Err(_) => return Err(format!("index `{}` is out of range", index)),
+                        }
+                    }
+                    (Some((Token::QuotedField, name)), Some((Token::CloseBracket, _))) => path
+                        .push(PathSegment::Field {
+                            name: from_quoted(&name[1..name.len() - 1]),
+                            quoted: true,
+                        }),
+                    _ => return Err("expected an index or a quoted field inside indexing".into()),
+                }
+                inner.next();
+                after_dot = false;
+                continue;
+            }
+            (Some((Token::Dot, _)), false) => {}
+            _ => break,
+        }
+        inner.next();
+        after_dot = !after_dot;
+    }
+    if after_dot && !path.is_empty() {
+        return Err("expected '[', an identifier, or a quoted key after '.'".into());
+    }
+
+    let comparison = match inner.next() {
+        None => None,
+        Some((token, slice)) => {
+            let comparison = match token {
+                Token::Eq => Comparison::Eq,
+                Token::Ne => Comparison::Ne,
+                Token::Lt => Comparison::Lt,
+                Token::Le => Comparison::Le,
+                Token::Gt => Comparison::Gt,
+                Token::Ge => Comparison::Ge,
+                token => {
+                    return Err(format!(
+                        "expected a comparison operator or ')', got {}",
+                        describe(*token, slice)
+                    ))
+                }
+            };
+            let literal = match inner.next() {
+                Some((token, slice)) => match parse_literal(*token, slice) {
+                    Some(literal) => literal,
+                    None => {
+                        return Err(format!(
+                            "expected a string, a number, a boolean, or null, got {}",
+                            describe(*token, slice)
+                        ))
+                    }
+                },
+                None => {
+                    return Err("expected a string, a number, a boolean, or null, got ')'".into())
+                }
+            };
+            Some((comparison, literal))
+        }
+    };
+    if let Some((token, slice)) = inner.next() {
+        return Err(format!("expected ')', got {}", describe(*token, slice)));
+    }
+
+    Ok(Predicate { path, comparison })
+}
+
+/// The alternatives of a query separated by `//`.
+#[derive(Debug)]
+pub(crate) struct Alternatives {
+    pub(crate) queries: Vec<QueryFragment>,
+    /// The literal after the last `//`, if any.
+    pub(crate) fallback: Option<Literal>,
+}
+
+pub(crate) fn parse(input: &str) -> (Alternatives, Vec<ParseError>) {
     let mut tokens = Token::lexer(input);
-    let mut queries = vec![];
     let mut errors = vec![];
+    check_escapes(input, &mut errors);
+    let mut alternatives = Alternatives {
+        queries: vec![],
+        fallback: None,
+    };
 
     loop {
-        match tokens.next() {
-            Some(Token::Dot) => {}
-            None => break,
+        let (fragment, next) = parse_path(&mut tokens, &mut errors);
+        alternatives.queries.push(fragment);
+        if !next {
+            break;
+        }
+
+        // a literal fallback ends the query
+        let mut lookahead = tokens.clone();
+        match lookahead.next() {
+            None => {
+                errors.push(ParseError::new(
+                    tokens.span(),
+                    "expected a query or a literal after '//', got EOF",
+                ));
+                break;
+            }
             Some(token) => {
-                errors.push(ParseError {
-                    message: format!(
-                        "{}..{}: expected {:?}, got {:?}",
-                        tokens.span().start,
-                        tokens.span().end,
-                        Token::Dot,
-                        token
-                    ),
-                });
-                continue;
+                if let Some(literal) = parse_literal(token, lookahead.slice()) {
+                    alternatives.fallback = Some(literal);
+                    tokens = lookahead;
+                    if tokens.next().is_some() {
+                        errors.push(ParseError::new(
+                            tokens.span(),
+                            "a literal fallback must be the last alternative",
+                        ));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    (alternatives, errors)
+}
+
+/// Parses an index or a slice bound, reporting a number that doesn't fit.
+fn parse_number<T: std::str::FromStr>(
+    slice: &str,
+    span: Range<usize>,
+    errors: &mut Vec<ParseError>,
+) -> Option<T> {
+    let number = slice.parse().ok();
+    if number.is_none() {
+        errors.push(ParseError::new(
+            span,
+            format!("index `{}` is out of range", slice),
+        ));
+    }
+    number
+}
+
+/// Parses the rest of an indexing after '[' and pushes the query.
+///
+/// Returns `false` if the input ends before ']'.
+fn parse_bracket(
+    tokens: &mut logos::Lexer<Token>,
+    queries: &mut Vec<Query>,
+    errors: &mut Vec<ParseError>,
+) -> bool {
+    let start = tokens.span().start;
+    let inner = {
+        let mut inner = vec![];
+        let mut closed = false;
+        while let Some(token) = tokens.next() {
+            if token == Token::CloseBracket {
+                closed = true;
+                break;
+            }
+
+            inner.push((token, tokens.slice()));
+        }
+        if !closed {
+            errors.push(ParseError::new(
+                start..tokens.span().end,
+                "expected an ']', got EOF",
+            ));
+            return false;
+        }
+        inner
+    };
+    let end = tokens.span().end;
+
+    match inner.as_slice() {
+        [(Token::Index | Token::NegativeIndex, slice)] => {
+            if let Some(index) = parse_number(slice, start..end, errors) {
+                queries.push(Query::Index(index))
             }
         }
+        [(Token::QuotedField, slice)] => {
+            let len = slice.len();
+            assert_eq!(&slice[0..1], "\"");
+            assert_eq!(&slice[len - 1..], "\"");
+            queries.push(Query::Field {
+                name: from_quoted(&slice[1..len - 1]),
+                quoted: true,
+            })
+        }
+        [] => queries.push(Query::CollectArray),
+        [(Token::Colon, _)] => queries.push(Query::Slice {
+            start: 0,
+            end: None,
+        }),
+        [(Token::Index, from), (Token::Colon, _)] => {
+            if let Some(from) = parse_number(from, start..end, errors) {
+                queries.push(Query::Slice {
+                    start: from,
+                    end: None,
+                })
+            }
+        }
+        [(Token::Colon, _), (Token::Index, to)] => {
+            if let Some(to) = parse_number(to, start..end, errors) {
+                queries.push(Query::Slice {
+                    start: 0,
+                    end: Some(to),
+                })
+            }
+        }
+        [(Token::Index, from), (Token::Colon, _), (Token::Index, to)] => {
+            let from = parse_number(from, start..end, errors);
+            let to = parse_number(to, start..end, errors);
+            if let (Some(from), Some(to)) = (from, to) {
+                queries.push(Query::Slice {
+                    start: from,
+                    end: Some(to),
+                })
+            }
+        }
+        [(token, slice), ..] => {
+            let error = ParseError::new(
+                start..end,
+                format!(
+                    "expected an index, a slice, or a quoted field inside indexing, got {}",
+                    describe(*token, slice)
+                ),
+            );
+            errors.push(match inner.as_slice() {
+                [(Token::Field, name)] => {
+                    error.with_help(format!("did you mean `[\"{}\"]`?", name))
+                }
+                _ => error,
+            });
+        }
+    }
+    true
+}
+
+/// Parses a path up to the next `//`.
+///
+/// Returns the query and whether another alternative follows.
+fn parse_path(
+    tokens: &mut logos::Lexer<Token>,
+    errors: &mut Vec<ParseError>,
+) -> (QueryFragment, bool) {
+    let mut queries = vec![];
+    let mut pipe = false;
+    let mut next = false;
 
+    loop {
+        let after_pipe = std::mem::take(&mut pipe);
         match tokens.next() {
-            Some(Token::OpenBracket) => {
-                let start = tokens.span().start;
-                let inner = {
-                    let mut inner = vec![];
-                    let mut closed = false;
-                    while let Some(token) = tokens.next() {
-                        if token == Token::CloseBracket {
-                            closed = true;
-                            break;
+            Some(token)
+                if token != Token::DoubleSlash
+                    && matches!(queries.last(), Some(Query::Function(_))) =>
+            {
+                errors.push(ParseError::new(
+                    tokens.span(),
+                    format!(
+                        "a built-in function must be at the end of the query, got {}",
+                        describe(token, tokens.slice())
+                    ),
+                ));
+                // skip to the next alternative
+                for token in tokens.by_ref() {
+                    if token == Token::DoubleSlash {
+                        next = true;
+                        break;
+                    }
+                }
+                break;
+            }
+            Some(Token::Dot) => {}
+            // brackets may directly follow the previous step as in jq: `.a[0]`, `.a["b"]`, `.a[]`
+            Some(Token::OpenBracket) if !after_pipe && !queries.is_empty() => {
+                if !parse_bracket(tokens, &mut queries, errors) {
+                    break;
+                }
+                continue;
+            }
+            // '?' marks the preceding field, index, or variant as optional
+            Some(Token::Question)
+                if !after_pipe
+                    && matches!(
+                        queries.last(),
+                        Some(Query::Field { .. } | Query::Index(_) | Query::Variant { .. })
+                    ) =>
+            {
+                queries.push(Query::Optional);
+                continue;
+            }
+            // '?' after '[]' or a slice marks the collection as optional
+            Some(Token::Question)
+                if !after_pipe
+                    && matches!(
+                        queries.last(),
+                        Some(Query::CollectArray | Query::Slice { .. })
+                    )
+                    && queries.iter().rev().nth(1) != Some(&Query::OptionalCollection) =>
+            {
+                queries.insert(queries.len() - 1, Query::OptionalCollection);
+                continue;
+            }
+            // '|' just separates the steps of the query
+            Some(Token::Pipe) if !after_pipe && !queries.is_empty() => {
+                pipe = true;
+                continue;
+            }
+            // a built-in function may start the query as in jq
+            Some(Token::Field)
+                if (after_pipe || queries.is_empty())
+                    && matches!(tokens.slice(), "length" | "keys" | "has" | "type") =>
+            {
+                let function = match tokens.slice() {
+                    "length" => Function::Length,
+                    "keys" => Function::Keys,
+                    "type" => Function::Type,
+                    _ => {
+                        let start = tokens.span().start;
+                        let open = tokens.next();
+                        let key = match tokens.next() {
+                            Some(Token::QuotedField) => {
+                                let slice = tokens.slice();
+                                Some(from_quoted(&slice[1..slice.len() - 1]))
+                            }
+                            _ => None,
+                        };
+                        let close = tokens.next();
+                        let argument = match (open, key, close) {
+                            (Some(Token::OpenParen), Some(key), Some(Token::CloseParen)) => {
+                                Some(key)
+                            }
+                            _ => None,
+                        };
+                        match argument {
+                            Some(key) => Function::Has(key),
+                            None => {
+                                errors.push(ParseError::new(
+                                    start..tokens.span().end,
+                                    "expected a quoted key as in 'has(\"key\")'",
+                                ));
+                                break;
+                            }
                         }
-
-                        inner.push((token, tokens.slice()));
                     }
-                    if !closed {
-                        errors.push(ParseError {
-                            message: format!(
-                                "{}..{}: expected an ']', got EOF",
-                                start,
-                                tokens.span().end,
-                            ),
-                        });
+                };
+                queries.push(Query::Function(function));
+                continue;
+            }
+            Some(Token::Field) if after_pipe && tokens.slice() == "select" => {
+                let start = tokens.span().start;
+                if tokens.next() != Some(Token::OpenParen) {
+                    errors.push(ParseError::new(
+                        start..tokens.span().end,
+                        "expected '(' after 'select'",
+                    ));
+                    break;
+                }
+                let mut inner = vec![];
+                let mut closed = false;
+                while let Some(token) = tokens.next() {
+                    if token == Token::CloseParen {
+                        closed = true;
                         break;
                     }
-                    inner
-                };
+                    inner.push((token, tokens.slice()));
+                }
+                if !closed {
+                    errors.push(ParseError::new(
+                        start..tokens.span().end,
+                        "expected ')', got EOF",
+                    ));
+                    break;
+                }
                 let end = tokens.span().end;
 
-                match inner.as_slice() {
-                    [(Token::Index, slice)] => queries.push(Query::Index(slice.parse().unwrap())),
-                    [(Token::QuotedField, slice)] => {
-                        let len = slice.len();
-                        assert_eq!(&slice[0..1], "\"");
-                        assert_eq!(&slice[len - 1..], "\"");
-                        queries.push(Query::Field {
-                            name: from_quoted(&slice[1..len - 1]),
-                            quoted: true,
-                        })
+                // A filtered-out value must not be reported as missing, so only collected values can be filtered.
+                if !matches!(
+                    queries.last(),
+                    Some(
+                        Query::CollectArray
+                            | Query::CollectMap
+                            | Query::Slice { .. }
+                            | Query::RecursiveField { .. }
+                            | Query::Select(_)
+                    )
+                ) {
+                    errors.push(ParseError::new(start..end, "select(...) must directly follow '.[]', '.[start:end]', '.{}', or '..field'"));
+                }
+                match parse_predicate(&inner) {
+                    Ok(predicate) => queries.push(Query::Select(predicate)),
+                    Err(message) => errors.push(ParseError::new(start..end, message)),
+                }
+                continue;
+            }
+            Some(Token::DotDot) => {
+                match tokens.next() {
+                    Some(Token::Field) => queries.push(Query::RecursiveField {
+                        name: tokens.slice().into(),
+                    }),
+                    None => {
+                        errors.push(ParseError::new(
+                            tokens.span(),
+                            "expected an identifier after '..', got EOF",
+                        ));
+                        break;
                     }
-                    [] => queries.push(Query::CollectArray),
-                    [(token, _), ..] => {
-                        errors.push(ParseError {
-                            message: format!(
-                                "{}..{}: expected an index or a quoted field inside indexing, got {:?}",
-                                start,
-                                end,
-                                token,
+                    Some(token) => {
+                        errors.push(ParseError::new(
+                            tokens.span(),
+                            format!(
+                                "expected an identifier after '..', got {}",
+                                describe(token, tokens.slice())
                             ),
-                        });
-                        continue;
+                        ));
                     }
                 }
+                continue;
             }
+            // '!' steps into an enum variant: `.event!Push.ref`
+            Some(Token::Bang) => {
+                match tokens.next() {
+                    Some(Token::Field) => queries.push(Query::Variant {
+                        name: tokens.slice().into(),
+                    }),
+                    None => {
+                        errors.push(ParseError::new(
+                            tokens.span(),
+                            "expected a variant name after '!', got EOF",
+                        ));
+                        break;
+                    }
+                    Some(token) => {
+                        errors.push(ParseError::new(
+                            tokens.span(),
+                            format!(
+                                "expected a variant name after '!', got {}",
+                                describe(token, tokens.slice())
+                            ),
+                        ));
+                    }
+                }
+                continue;
+            }
+            None | Some(Token::DoubleSlash) if after_pipe => {
+                errors.push(ParseError::new(tokens.span(), "expected a query after '|'"));
+                break;
+            }
+            Some(Token::DoubleSlash) if queries.is_empty() => {
+                errors.push(ParseError::new(
+                    tokens.span(),
+                    "expected a query before '//'",
+                ));
+                next = true;
+                break;
+            }
+            Some(Token::DoubleSlash) => {
+                next = true;
+                break;
+            }
+            None => break,
+            Some(token) => {
+                let error = ParseError::new(
+                    tokens.span(),
+                    format!(
+                        "expected {}, got {}",
+                        Token::Dot,
+                        describe(token, tokens.slice())
+                    ),
+                );
+                let slice = tokens.slice();
+                errors.push(match token {
+                    Token::Field
+                        if matches!(slice, "select" | "length" | "keys" | "has" | "type") =>
+                    {
+                        error.with_help(format!("did you mean `| {}`?", slice))
+                    }
+                    Token::Field => error.with_help(format!("did you mean `.{}`?", slice)),
+                    _ => error,
+                });
+                continue;
+            }
+        }
+
+        match tokens.next() {
+            Some(Token::OpenBracket) => {
+                if !parse_bracket(tokens, &mut queries, errors) {
+                    break;
+                }
+            }
+            // ."quoted key"
+            Some(Token::QuotedField) => {
+                let slice = tokens.slice();
+                queries.push(Query::Field {
+                    name: from_quoted(&slice[1..slice.len() - 1]),
+                    quoted: true,
+                })
+            }
+            Some(Token::OpenBrace) => match tokens.next() {
+                Some(Token::CloseBrace) => queries.push(Query::CollectMap),
+                None => {
+                    errors.push(ParseError::new(tokens.span(), "expected '}', got EOF"));
+                    break;
+                }
+                Some(token) => {
+                    errors.push(ParseError::new(
+                        tokens.span(),
+                        format!("expected '}}', got {}", describe(token, tokens.slice())),
+                    ));
+                    continue;
+                }
+            },
             Some(Token::Field) => queries.push(Query::Field {
                 name: tokens.slice().into(),
                 quoted: false,
             }),
             None => {
-                errors.push(ParseError {
-                    message: format!(
-                        "{}..{}: expected '[' or an identifier, got EOF",
-                        tokens.span().start,
-                        tokens.span().end,
-                    ),
-                });
+                errors.push(ParseError::new(
+                    tokens.span(),
+                    "expected '[', '{}', an identifier, or a quoted key, got EOF",
+                ));
                 break;
             }
             Some(token) => {
-                errors.push(ParseError {
-                    message: format!(
-                        "{}..{}: expected '[' or an identifier, got {:?}",
-                        tokens.span().start,
-                        tokens.span().end,
-                        token
+                let error = ParseError::new(
+                    tokens.span(),
+                    format!(
+                        "expected '[', '{{}}', an identifier, or a quoted key, got {}",
+                        describe(token, tokens.slice())
                     ),
+                );
+                errors.push(match token {
+                    Token::Index | Token::NegativeIndex => {
+                        error.with_help(format!("did you mean `.[{}]`?", tokens.slice()))
+                    }
+                    _ => error,
                 });
                 continue;
             }
         }
     }
 
-    let fragment =
-        queries
-            .into_iter()
-            .rev()
-            .fold(QueryFragment::accept(), |rest, query| match query {
-                Query::Field { name, quoted } => QueryFragment::field(name, quoted, rest),
-                Query::Index(index) => QueryFragment::index_array(index, rest),
-                Query::CollectArray => QueryFragment::collect_array(rest),
-            });
-    (fragment, errors)
+    (lower(queries), next)
+}
+
+/// Builds the query fragment for a path.
+pub(crate) fn lower(queries: Vec<Query>) -> QueryFragment {
+    queries
+        .into_iter()
+        .rev()
+        .fold(QueryFragment::accept(), |rest, query| match query {
+            Query::Field { name, quoted } => QueryFragment::field(name, quoted, rest),
+            Query::Index(index) => QueryFragment::index_array(index, rest),
+            Query::CollectArray => QueryFragment::collect_array(rest),
+            Query::CollectMap => QueryFragment::collect_map(rest),
+            Query::Slice { start, end } => QueryFragment::slice_array(start, end, rest),
+            Query::RecursiveField { name } => QueryFragment::recursive_field(name, rest),
+            Query::Variant { name } => QueryFragment::variant(name, rest),
+            Query::Select(predicate) => QueryFragment::select(predicate, rest),
+            Query::Function(function) => QueryFragment::function(function),
+            Query::Optional => QueryFragment::optional(rest),
+            Query::OptionalCollection => QueryFragment::optional_collection(rest),
+        })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// Parses a query without alternatives.
+    fn parse(input: &str) -> (QueryFragment, Vec<ParseError>) {
+        let (mut alternatives, errors) = super::parse(input);
+        assert_eq!(alternatives.queries.len(), 1);
+        assert_eq!(alternatives.fallback, None);
+        (alternatives.queries.pop().unwrap(), errors)
+    }
+
     #[test]
     fn lexer() {
         use Token::*;
@@ -216,4 +965,533 @@ mod test {
         );
         assert!(errors.is_empty());
     }
+
+    #[test]
+    fn parser_negative_index() {
+        let (query, errors) = parse(r#".[-1].name"#);
+        assert_eq!(
+            query,
+            QueryFragment::index_array(
+                -1,
+                QueryFragment::field("name".into(), false, QueryFragment::accept())
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".[-1:]"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_index_out_of_range() {
+        let (_, errors) = parse(r#".[99999999999999999999999]"#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, 1..26);
+        assert_eq!(
+            errors[0].message,
+            "index `99999999999999999999999` is out of range"
+        );
+
+        let (_, errors) = parse(r#".[99999999999999999999999:]"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".[:99999999999999999999999]"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".[99999999999999999999999:99999999999999999999999]"#);
+        assert_eq!(errors.len(), 2);
+
+        let (_, errors) = parse(r#".[] | select(.[99999999999999999999999] == 1)"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_recursive_field() {
+        let (query, errors) = parse(r#"..version"#);
+        assert_eq!(
+            query,
+            QueryFragment::recursive_field("version".into(), QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".config..metadata.name"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "config".into(),
+                false,
+                QueryFragment::recursive_field(
+                    "metadata".into(),
+                    QueryFragment::field("name".into(), false, QueryFragment::accept())
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#"..[0]"#);
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn parser_collect_map() {
+        let (query, errors) = parse(r#".packages.{}.version"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "packages".into(),
+                false,
+                QueryFragment::collect_map(QueryFragment::field(
+                    "version".into(),
+                    false,
+                    QueryFragment::accept()
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".{"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_variant() {
+        let (query, errors) = parse(r#".event!Push?.ref"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "event".into(),
+                false,
+                QueryFragment::variant(
+                    "Push".into(),
+                    QueryFragment::optional(QueryFragment::field(
+                        "ref".into(),
+                        false,
+                        QueryFragment::accept()
+                    ))
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#"!Named"#);
+        assert_eq!(
+            query,
+            QueryFragment::variant("Named".into(), QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".event!"#);
+        assert_eq!(errors.len(), 1);
+        let (_, errors) = parse(r#".event!"Push""#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_select() {
+        let (query, errors) =
+            parse(r#".items.[] | select(.kind == "Deployment") | .metadata.name"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "items".into(),
+                false,
+                QueryFragment::collect_array(QueryFragment::select(
+                    Predicate {
+                        path: vec![PathSegment::Field {
+                            name: "kind".into(),
+                            quoted: false
+                        }],
+                        comparison: Some((Comparison::Eq, Literal::String("Deployment".into()))),
+                    },
+                    QueryFragment::field(
+                        "metadata".into(),
+                        false,
+                        QueryFragment::field("name".into(), false, QueryFragment::accept())
+                    )
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".[] | select(.) | select(.["a b"].[0] >= -1.5)"#);
+        assert_eq!(
+            query,
+            QueryFragment::collect_array(QueryFragment::select(
+                Predicate {
+                    path: vec![],
+                    comparison: None,
+                },
+                QueryFragment::select(
+                    Predicate {
+                        path: vec![
+                            PathSegment::Field {
+                                name: "a b".into(),
+                                quoted: true
+                            },
+                            PathSegment::Index(0)
+                        ],
+                        comparison: Some((Comparison::Ge, Literal::Number("-1.5".into()))),
+                    },
+                    QueryFragment::accept()
+                )
+            ))
+        );
+        assert!(errors.is_empty());
+
+        // select must follow a collector
+        let (_, errors) = parse(r#".a | select(.b)"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".[] | select(.b ==)"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".[] | select(.b"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".[] |"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_alternatives() {
+        let (alternatives, errors) = super::parse(r#".user.username // .user.login"#);
+        assert_eq!(
+            alternatives.queries,
+            [
+                QueryFragment::field(
+                    "user".into(),
+                    false,
+                    QueryFragment::field("username".into(), false, QueryFragment::accept())
+                ),
+                QueryFragment::field(
+                    "user".into(),
+                    false,
+                    QueryFragment::field("login".into(), false, QueryFragment::accept())
+                ),
+            ]
+        );
+        assert_eq!(alternatives.fallback, None);
+        assert!(errors.is_empty());
+
+        let (alternatives, errors) = super::parse(r#".retries // .attempts // 3"#);
+        assert_eq!(alternatives.queries.len(), 2);
+        assert_eq!(alternatives.fallback, Some(Literal::Number("3".into())));
+        assert!(errors.is_empty());
+
+        let (alternatives, errors) = super::parse(r#".name // "unknown""#);
+        assert_eq!(alternatives.queries.len(), 1);
+        assert_eq!(
+            alternatives.fallback,
+            Some(Literal::String("unknown".into()))
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = super::parse(r#".a // 3 // .b"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = super::parse(r#"// .a"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = super::parse(r#".a //"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_function() {
+        let (query, errors) = parse(r#".items | length"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "items".into(),
+                false,
+                QueryFragment::function(Function::Length)
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".[] | has("debug")"#);
+        assert_eq!(
+            query,
+            QueryFragment::collect_array(QueryFragment::function(Function::Has("debug".into())))
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#"keys"#);
+        assert_eq!(query, QueryFragment::function(Function::Keys));
+        assert!(errors.is_empty());
+
+        let (alternatives, errors) = super::parse(r#".a | type // "missing""#);
+        assert_eq!(
+            alternatives.queries,
+            [QueryFragment::field(
+                "a".into(),
+                false,
+                QueryFragment::function(Function::Type)
+            )]
+        );
+        assert!(errors.is_empty());
+
+        // the field named 'length'
+        let (query, errors) = parse(r#".length"#);
+        assert_eq!(
+            query,
+            QueryFragment::field("length".into(), false, QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".a | length.b"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".a | has(0)"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_slice() {
+        let (query, errors) = parse(r#".[2:5]"#);
+        assert_eq!(
+            query,
+            QueryFragment::slice_array(2, Some(5), QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".[:10].name"#);
+        assert_eq!(
+            query,
+            QueryFragment::slice_array(
+                0,
+                Some(10),
+                QueryFragment::field("name".into(), false, QueryFragment::accept())
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".[5:]"#);
+        assert_eq!(
+            query,
+            QueryFragment::slice_array(5, None, QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".[1:2:3]"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_optional() {
+        let (query, errors) = parse(r#".author?.name"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "author".into(),
+                false,
+                QueryFragment::optional(QueryFragment::field(
+                    "name".into(),
+                    false,
+                    QueryFragment::accept()
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".[0]?"#);
+        assert_eq!(
+            query,
+            QueryFragment::index_array(0, QueryFragment::optional(QueryFragment::accept()))
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".?"#);
+        assert_eq!(errors.len(), 1);
+
+        let (query, errors) = parse(r#".[]?"#);
+        assert_eq!(
+            query,
+            QueryFragment::optional_collection(QueryFragment::collect_array(
+                QueryFragment::accept()
+            ))
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#".[]??"#);
+        assert_eq!(errors.len(), 1);
+
+        let (_, errors) = parse(r#".a??"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parser_escape() {
+        let (query, errors) = parse(r#".["a\"b\\c\/\n\té😀"]"#);
+        assert_eq!(
+            query,
+            QueryFragment::field("a\"b\\c/\n\té😀".into(), true, QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+
+        let (_, errors) = parse(r#"."\x""#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            r#"2..4: invalid escape sequence '\x'"#
+        );
+
+        let (_, errors) = parse(r#".["ab\u12"]"#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            r#"5..9: expected 4 hex digits after '\u'"#
+        );
+
+        let (_, errors) = super::parse(r#".["\uD83Dx"] // .["\uDE00"]"#);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "3..9: a high surrogate must be followed by a low surrogate"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "19..25: a low surrogate must follow a high surrogate"
+        );
+    }
+
+    #[test]
+    fn unescape_sequences() {
+        assert_eq!(unescape(r#"caf\u00e9"#, '"').unwrap(), "café");
+        assert_eq!(unescape(r#"\uD83D\uDE00"#, '"').unwrap(), "😀");
+        assert_eq!(unescape(r#"a\nb\tc"#, '"').unwrap(), "a\nb\tc");
+        assert_eq!(unescape(r#"a\/b"#, '"').unwrap(), "a/b");
+        assert_eq!(unescape(r#"\b\f\r"#, '"').unwrap(), "\u{8}\u{c}\r");
+        assert_eq!(unescape(r#"it\'s"#, '\'').unwrap(), "it's");
+
+        let (query, errors) = parse(r#"."caf\u00e9 \uD83D\uDE00""#);
+        assert_eq!(
+            query,
+            QueryFragment::field("café 😀".into(), true, QueryFragment::accept())
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn parser_help() {
+        let (_, errors) = parse(r#".items.0"#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "7..8: expected '[', '{}', an identifier, or a quoted key, got an index `0`"
+        );
+        assert_eq!(errors[0].help.as_deref(), Some("did you mean `.[0]`?"));
+
+        let (_, errors) = parse(r#".[kubernetes_clusters]"#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, 1..22);
+        assert_eq!(
+            errors[0].help.as_deref(),
+            Some(r#"did you mean `["kubernetes_clusters"]`?"#)
+        );
+
+        let (_, errors) = parse(r#".items.[] select(.x)"#);
+        assert_eq!(
+            errors[0].to_string(),
+            "10..16: expected '.', got an identifier `select`"
+        );
+        assert_eq!(errors[0].help.as_deref(), Some("did you mean `| select`?"));
+
+        let (_, errors) = parse(r#".a b"#);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].help.as_deref(), Some("did you mean `.b`?"));
+    }
+
+    #[test]
+    fn parser_postfix() {
+        let (query, errors) = parse(r#".commits[0]["author"].name"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "commits".into(),
+                false,
+                QueryFragment::index_array(
+                    0,
+                    QueryFragment::field(
+                        "author".into(),
+                        true,
+                        QueryFragment::field("name".into(), false, QueryFragment::accept())
+                    )
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".a[][1:]?."b c""#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "a".into(),
+                false,
+                QueryFragment::collect_array(QueryFragment::optional_collection(
+                    QueryFragment::slice_array(
+                        1,
+                        None,
+                        QueryFragment::field("b c".into(), true, QueryFragment::accept())
+                    )
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".a[]? | .[1:]?"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "a".into(),
+                false,
+                QueryFragment::optional_collection(QueryFragment::collect_array(
+                    QueryFragment::optional_collection(QueryFragment::slice_array(
+                        1,
+                        None,
+                        QueryFragment::accept()
+                    ))
+                ))
+            )
+        );
+        assert!(errors.is_empty());
+
+        let (query, errors) = parse(r#".a[0]? | .[] | select(.x[1]."y" == 1)"#);
+        assert_eq!(
+            query,
+            QueryFragment::field(
+                "a".into(),
+                false,
+                QueryFragment::index_array(
+                    0,
+                    QueryFragment::optional(QueryFragment::collect_array(QueryFragment::select(
+                        Predicate {
+                            path: vec![
+                                PathSegment::Field {
+                                    name: "x".into(),
+                                    quoted: false
+                                },
+                                PathSegment::Index(1),
+                                PathSegment::Field {
+                                    name: "y".into(),
+                                    quoted: true
+                                },
+                            ],
+                            comparison: Some((Comparison::Eq, Literal::Number("1".into()))),
+                        },
+                        QueryFragment::accept()
+                    )))
+                )
+            )
+        );
+        assert!(errors.is_empty());
+
+        // a bracket cannot start a query or a step after '|'
+        let (_, errors) = parse(r#"[0]"#);
+        assert!(!errors.is_empty());
+        let (_, errors) = parse(r#".a | [0]"#);
+        assert!(!errors.is_empty());
+        let (_, errors) = parse(r#".a[0"#);
+        assert_eq!(errors.len(), 1);
+    }
 }
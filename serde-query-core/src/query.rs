@@ -1,5 +1,107 @@
+use std::fmt;
+
 use proc_macro2::TokenStream;
 
+/// A step of the path inside `select(...)`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum PathSegment {
+    Field { name: String, quoted: bool },
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Literal {
+    Null,
+    Bool(bool),
+    /// The number as written in the query.
+    Number(String),
+    String(String),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Null => f.write_str("null"),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Number(n) => f.write_str(n),
+            Literal::String(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// '.' [<path>] [<comparison> <literal>]
+///
+/// Without a comparison, the predicate holds if the value at the path is neither null nor false.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Predicate {
+    pub(crate) path: Vec<PathSegment>,
+    pub(crate) comparison: Option<(Comparison, Literal)>,
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(".")?;
+        }
+        for segment in self.path.iter() {
+            match segment {
+                PathSegment::Field {
+                    name,
+                    quoted: false,
+                } => write!(f, ".{}", name)?,
+                PathSegment::Field { name, quoted: true } => write!(f, ".[{:?}]", name)?,
+                PathSegment::Index(index) => write!(f, ".[{}]", index)?,
+            }
+        }
+        if let Some((comparison, literal)) = &self.comparison {
+            let comparison = match comparison {
+                Comparison::Eq => "==",
+                Comparison::Ne => "!=",
+                Comparison::Lt => "<",
+                Comparison::Le => "<=",
+                Comparison::Gt => ">",
+                Comparison::Ge => ">=",
+            };
+            write!(f, " {} {}", comparison, literal)?;
+        }
+        Ok(())
+    }
+}
+
+/// A built-in function at the end of a query.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Function {
+    /// The number of elements, entries, or characters.
+    Length,
+    /// The keys of a map or the indices of a sequence.
+    Keys,
+    /// Whether a map has the key.
+    Has(String),
+    /// The type of the value.
+    Type,
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Function::Length => f.write_str("length"),
+            Function::Keys => f.write_str("keys"),
+            Function::Has(key) => write!(f, "has({:?})", key),
+            Function::Type => f.write_str("type"),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum QueryFragment {
     Accept,
@@ -10,14 +112,69 @@ pub(crate) enum QueryFragment {
         rest: Box<QueryFragment>,
     },
     /// '.' '[' <n> ']' [.<rest>]
+    ///
+    /// A negative index counts from the end of the sequence.
     IndexArray {
-        index: usize,
+        index: isize,
         rest: Box<QueryFragment>,
     },
     /// '.[]' [.<rest>]
+    ///
+    /// Runs the rest of the query for each element of a sequence or each value of a map.
     CollectArray {
         rest: Box<QueryFragment>,
     },
+    /// '.{}' [.<rest>]
+    ///
+    /// Runs the rest of the query for each value in a map, keeping the keys.
+    CollectMap {
+        rest: Box<QueryFragment>,
+    },
+    /// '.' '[' [<start>] ':' [<end>] ']' [.<rest>]
+    SliceArray {
+        start: usize,
+        end: Option<usize>,
+        rest: Box<QueryFragment>,
+    },
+    /// '..' <name> [.<rest>]
+    ///
+    /// Runs the rest of the query for every field named <name> at any depth.
+    RecursiveField {
+        name: String,
+        rest: Box<QueryFragment>,
+    },
+    /// '!' <name> [.<rest>]
+    ///
+    /// Runs the rest of the query on the payload of the enum variant <name>.
+    Variant {
+        name: String,
+        rest: Box<QueryFragment>,
+    },
+    /// '|' 'select' '(' <predicate> ')' [.<rest>]
+    ///
+    /// Runs the rest of the query only if the predicate holds for the value.
+    Select {
+        predicate: Predicate,
+        rest: Box<QueryFragment>,
+    },
+    /// '|' <function>
+    ///
+    /// Runs a built-in function instead of deserializing the value.
+    Function {
+        function: Function,
+    },
+    /// '?' [.<rest>]
+    ///
+    /// The value reached by the preceding segment may be missing or null.
+    Optional {
+        rest: Box<QueryFragment>,
+    },
+    /// '?' after '[]' or '[<start>:<end>]'
+    ///
+    /// The collection may be missing or null, in which case it evaluates to an empty container.
+    OptionalCollection {
+        rest: Box<QueryFragment>,
+    },
 }
 
 impl QueryFragment {
@@ -33,7 +190,7 @@ impl QueryFragment {
         }
     }
 
-    pub(crate) fn index_array(index: usize, rest: Self) -> Self {
+    pub(crate) fn index_array(index: isize, rest: Self) -> Self {
         Self::IndexArray {
             index,
             rest: rest.into(),
@@ -43,18 +200,91 @@ impl QueryFragment {
     pub(crate) fn collect_array(rest: Self) -> Self {
         Self::CollectArray { rest: rest.into() }
     }
+
+    pub(crate) fn collect_map(rest: Self) -> Self {
+        Self::CollectMap { rest: rest.into() }
+    }
+
+    pub(crate) fn slice_array(start: usize, end: Option<usize>, rest: Self) -> Self {
+        Self::SliceArray {
+            start,
+            end,
+            rest: rest.into(),
+        }
+    }
+
+    pub(crate) fn recursive_field(name: String, rest: Self) -> Self {
+        Self::RecursiveField {
+            name,
+            rest: rest.into(),
+        }
+    }
+
+    pub(crate) fn variant(name: String, rest: Self) -> Self {
+        Self::Variant {
+            name,
+            rest: rest.into(),
+        }
+    }
+
+    pub(crate) fn select(predicate: Predicate, rest: Self) -> Self {
+        Self::Select {
+            predicate,
+            rest: rest.into(),
+        }
+    }
+
+    pub(crate) fn function(function: Function) -> Self {
+        Self::Function { function }
+    }
+
+    pub(crate) fn optional(rest: Self) -> Self {
+        Self::Optional { rest: rest.into() }
+    }
+
+    pub(crate) fn optional_collection(rest: Self) -> Self {
+        Self::OptionalCollection { rest: rest.into() }
+    }
 }
 
+/// How to deserialize the value at the end of a query.
+#[derive(Debug, Clone)]
+pub(crate) enum DeserializeWith {
+    /// `Deserialize::deserialize`.
+    Deserialize,
+    /// `DeserializeBorrowed::deserialize_borrowed`, which borrows `Cow` from the input.
+    Borrow,
+    /// The function of `#[query("...", deserialize_with = "...")]` or `with = "..."`.
+    Function(TokenStream),
+    /// `DeserializeNested::deserialize_nested` for `#[query("...", nested)]`, which keeps the
+    /// errors of the queries in the nested struct.
+    Nested,
+}
+
+/// Identifies a query.
+///
+/// A field has a query for each alternative separated by `//`. The identifier names the slot for the
+/// result of the query, and the field name is used in error messages.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct QueryId(syn::Ident);
+pub(crate) struct QueryId {
+    ident: syn::Ident,
+    field: String,
+}
 
 impl QueryId {
-    pub(crate) fn new(identifier: syn::Ident) -> Self {
-        Self(identifier)
+    pub(crate) fn new(identifier: syn::Ident, field: String) -> Self {
+        Self {
+            ident: identifier,
+            field,
+        }
     }
 
     pub(crate) fn ident(&self) -> &syn::Ident {
-        &self.0
+        &self.ident
+    }
+
+    pub(crate) fn field(&self) -> &str {
+        &self.field
     }
 }
 
@@ -63,10 +293,44 @@ pub(crate) struct Query {
     pub(crate) id: QueryId,
     pub(crate) fragment: QueryFragment,
     pub(crate) ty: TokenStream,
+    pub(crate) deserialize_with: DeserializeWith,
+    /// Whether the field takes its default if the value is missing.
+    pub(crate) default: bool,
 }
 
 impl Query {
-    pub(crate) fn new(id: QueryId, fragment: QueryFragment, ty: TokenStream) -> Self {
-        Self { id, fragment, ty }
+    pub(crate) fn new(
+        id: QueryId,
+        fragment: QueryFragment,
+        ty: TokenStream,
+        deserialize_with: DeserializeWith,
+        default: bool,
+    ) -> Self {
+        Self {
+            id,
+            fragment,
+            ty,
+            deserialize_with,
+            default,
+        }
     }
 }
+
+/// The queries for a struct field.
+///
+/// The field takes the result of the first query that succeeds, or the fallback if every query fails.
+pub(crate) struct FieldQueries {
+    /// The name of the local variable for the field, e.g. `name` or `__field0` for a tuple field.
+    pub(crate) ident: syn::Ident,
+    /// The field in the struct expression, e.g. `name` or `0`.
+    pub(crate) member: syn::Member,
+    /// The type of the field.
+    pub(crate) ty: syn::Type,
+    /// How the field deserializes the values, shared by its queries.
+    pub(crate) deserialize_with: DeserializeWith,
+    pub(crate) queries: Vec<QueryId>,
+    pub(crate) fallback: Option<Literal>,
+    /// The expression of `#[query("...", default)]` or `#[query("...", default = "...")]`.
+    /// The last query takes it if the value is missing.
+    pub(crate) default: Option<TokenStream>,
+}
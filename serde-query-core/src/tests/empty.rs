@@ -27,8 +27,13 @@ const _: () = {
             core::result::Result::Ok(())
         }
     }
-    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for EmptyInput {
-        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    impl<'de> serde_query::__priv::DeserializeNested<'de> for EmptyInput {
+        fn deserialize_nested<D>(
+            deserializer: D,
+        ) -> core::result::Result<
+            core::result::Result<Self, serde_query::__priv::Errors>,
+            D::Error,
+        >
         where
             D: serde_query::__priv::serde::de::Deserializer<'de>,
         {
@@ -39,17 +44,34 @@ const _: () = {
             let has_error = false;
             if !has_error {
                 let value = EmptyInput {};
-                core::result::Result::Ok(value)
+                core::result::Result::Ok(core::result::Result::Ok(value))
             } else {
                 let errors = [];
-                core::result::Result::Err(
-                    <D::Error as serde_query::__priv::serde::de::Error>::custom(
-                        serde_query::__priv::Errors::new(&errors),
-                    ),
+                core::result::Result::Ok(
+                    core::result::Result::Err(serde_query::__priv::Errors::new(errors)),
                 )
             }
         }
     }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for EmptyInput {
+        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            match <Self as serde_query::__priv::DeserializeNested<
+                'de,
+            >>::deserialize_nested(deserializer)? {
+                core::result::Result::Ok(value) => core::result::Result::Ok(value),
+                core::result::Result::Err(errors) => {
+                    core::result::Result::Err(
+                        <D::Error as serde_query::__priv::serde::de::Error>::custom(
+                            errors,
+                        ),
+                    )
+                }
+            }
+        }
+    }
 };
 
 "#
@@ -36,7 +36,7 @@ const _: () = {
                 x: self.x,
                 y: self.y,
             };
-            deserializer.deserialize_map(visitor)?;
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
             if self.x.is_none() {
                 *self
                     .x = core::option::Option::Some(
@@ -137,6 +137,73 @@ const _: () = {
             }
             core::result::Result::Ok(())
         }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "locs" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode2 {
+                            x: &mut *self.x,
+                            y: &mut *self.y,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "x",
+                                ".",
+                                serde_query::__priv::variant_mismatch("locs", &name),
+                            ),
+                        ),
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "y",
+                                ".",
+                                serde_query::__priv::variant_mismatch("locs", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "locs" {
+                if self.x.is_none() {
+                    *self
+                        .x = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "x",
+                                ".",
+                                serde_query::__priv::variant_mismatch("locs", &name),
+                            ),
+                        ),
+                    );
+                }
+                if self.y.is_none() {
+                    *self
+                        .y = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "y",
+                                ".",
+                                serde_query::__priv::variant_mismatch("locs", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
     }
     enum FieldNode0 {
         Field0,
@@ -200,7 +267,7 @@ const _: () = {
                 x: &mut x,
                 y: &mut y,
             };
-            deserializer.deserialize_seq(visitor)?;
+            serde_query::__priv::deserialize_seq_or_map(deserializer, visitor)?;
             *self.x = core::option::Option::Some(x);
             *self.y = core::option::Option::Some(y);
             core::result::Result::Ok(())
@@ -214,7 +281,7 @@ const _: () = {
     for VisitorNode2<'query> {
         type Value = ();
         fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-            core::fmt::Formatter::write_str(formatter, "a sequence")
+            core::fmt::Formatter::write_str(formatter, "a sequence or a map")
         }
         fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
         where
@@ -261,7 +328,7 @@ const _: () = {
                                     ) => {
                                         *self.x = core::result::Result::Err(e);
                                     }
-                                    core::option::Option::None => unreachable!(),
+                                    core::option::Option::None => {}
                                 }
                             }
                             core::result::Result::Err(_) => {}
@@ -282,7 +349,7 @@ const _: () = {
                                     ) => {
                                         *self.y = core::result::Result::Err(e);
                                     }
-                                    core::option::Option::None => unreachable!(),
+                                    core::option::Option::None => {}
                                 }
                             }
                             core::result::Result::Err(_) => {}
@@ -292,6 +359,73 @@ const _: () = {
             }
             core::result::Result::Ok(())
         }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            if let core::option::Option::Some(additional) = map.size_hint() {
+                <Vec<
+                    f32,
+                > as serde_query::__priv::Container>::reserve(
+                    self.x.as_mut().unwrap(),
+                    additional,
+                );
+                <Vec<
+                    f32,
+                > as serde_query::__priv::Container>::reserve(
+                    self.y.as_mut().unwrap(),
+                    additional,
+                );
+            }
+            while map.next_key::<serde_query::__priv::serde::de::IgnoredAny>()?.is_some()
+            {
+                let mut x = core::option::Option::None;
+                let mut y = core::option::Option::None;
+                map.next_value_seed(DeserializeSeedNode3 {
+                    x: &mut x,
+                    y: &mut y,
+                })?;
+                match &mut self.x {
+                    core::result::Result::Ok(ref mut container) => {
+                        match x {
+                            core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                <Vec<
+                                    f32,
+                                > as serde_query::__priv::Container>::extend_one(
+                                    container,
+                                    v,
+                                )
+                            }
+                            core::option::Option::Some(core::result::Result::Err(e)) => {
+                                *self.x = core::result::Result::Err(e);
+                            }
+                            core::option::Option::None => {}
+                        }
+                    }
+                    core::result::Result::Err(_) => {}
+                }
+                match &mut self.y {
+                    core::result::Result::Ok(ref mut container) => {
+                        match y {
+                            core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                <Vec<
+                                    f32,
+                                > as serde_query::__priv::Container>::extend_one(
+                                    container,
+                                    v,
+                                )
+                            }
+                            core::option::Option::Some(core::result::Result::Err(e)) => {
+                                *self.y = core::result::Result::Err(e);
+                            }
+                            core::option::Option::None => {}
+                        }
+                    }
+                    core::result::Result::Err(_) => {}
+                }
+            }
+            core::result::Result::Ok(())
+        }
     }
     struct DeserializeSeedNode3<'query> {
         x: &'query mut core::option::Option<
@@ -318,7 +452,7 @@ const _: () = {
                 x: self.x,
                 y: self.y,
             };
-            deserializer.deserialize_map(visitor)?;
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
             if self.x.is_none() {
                 *self
                     .x = core::option::Option::Some(
@@ -428,6 +562,82 @@ const _: () = {
             }
             core::result::Result::Ok(())
         }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "x" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode4 {
+                            x: &mut *self.x,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                "y" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode8 {
+                            y: &mut *self.y,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "x",
+                                ".locs.[]",
+                                serde_query::__priv::variant_mismatch("x", &name),
+                            ),
+                        ),
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "y",
+                                ".locs.[]",
+                                serde_query::__priv::variant_mismatch("y", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "x" {
+                if self.x.is_none() {
+                    *self
+                        .x = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "x",
+                                ".locs.[]",
+                                serde_query::__priv::variant_mismatch("x", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "y" {
+                if self.y.is_none() {
+                    *self
+                        .y = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "y",
+                                ".locs.[]",
+                                serde_query::__priv::variant_mismatch("y", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
     }
     enum FieldNode3 {
         Field0,
@@ -548,8 +758,13 @@ const _: () = {
             core::result::Result::Ok(())
         }
     }
-    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for Locations {
-        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    impl<'de> serde_query::__priv::DeserializeNested<'de> for Locations {
+        fn deserialize_nested<D>(
+            deserializer: D,
+        ) -> core::result::Result<
+            core::result::Result<Self, serde_query::__priv::Errors>,
+            D::Error,
+        >
         where
             D: serde_query::__priv::serde::de::Deserializer<'de>,
         {
@@ -570,17 +785,34 @@ const _: () = {
                     x: x.unwrap(),
                     y: y.unwrap(),
                 };
-                core::result::Result::Ok(value)
+                core::result::Result::Ok(core::result::Result::Ok(value))
             } else {
                 let errors = [x.err(), y.err()];
-                core::result::Result::Err(
-                    <D::Error as serde_query::__priv::serde::de::Error>::custom(
-                        serde_query::__priv::Errors::new(&errors),
-                    ),
+                core::result::Result::Ok(
+                    core::result::Result::Err(serde_query::__priv::Errors::new(errors)),
                 )
             }
         }
     }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for Locations {
+        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            match <Self as serde_query::__priv::DeserializeNested<
+                'de,
+            >>::deserialize_nested(deserializer)? {
+                core::result::Result::Ok(value) => core::result::Result::Ok(value),
+                core::result::Result::Err(errors) => {
+                    core::result::Result::Err(
+                        <D::Error as serde_query::__priv::serde::de::Error>::custom(
+                            errors,
+                        ),
+                    )
+                }
+            }
+        }
+    }
 };
 
 "#
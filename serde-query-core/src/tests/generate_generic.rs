@@ -0,0 +1,1474 @@
+use syn::DeriveInput;
+
+use crate::{tests::snapshot_derive, DeriveTarget};
+
+#[test]
+fn test_generic() {
+    let input: DeriveInput = syn::parse_quote! {
+        struct Page<T> {
+            #[query(".data.items[]")]
+            items: Vec<T>,
+            #[query(".meta.next")]
+            next: Option<String>,
+        }
+    };
+
+    k9::snapshot!(
+        snapshot_derive(input, DeriveTarget::Deserialize),
+        r#"
+const _: () = {
+    struct DeserializeSeedNode0<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        >,
+        next: &'query mut core::option::Option<
+            core::result::Result<Option<String>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode0<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let visitor = VisitorNode0::<'_, T> {
+                __marker: core::marker::PhantomData,
+                items: self.items,
+                next: self.next,
+            };
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
+            if self.items.is_none() {
+                *self
+                    .items = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "items",
+                            ".",
+                            "missing field 'data'",
+                        ),
+                    ),
+                );
+            }
+            if self.next.is_none() {
+                *self
+                    .next = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "next",
+                            ".",
+                            "missing field 'meta'",
+                        ),
+                    ),
+                );
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode0<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        >,
+        next: &'query mut core::option::Option<
+            core::result::Result<Option<String>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode0<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                formatter,
+                "one of the following fields: 'data', or 'meta'",
+            )
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            while let core::option::Option::Some(key) = map.next_key::<FieldNode0>()? {
+                match key {
+                    FieldNode0::Field0 => {
+                        let mut items = core::option::Option::None;
+                        let items = match &mut self.items {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .items = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "items",
+                                            ".",
+                                            "duplicated field 'data'",
+                                        ),
+                                    ),
+                                );
+                                &mut items
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut items
+                            }
+                            core::option::Option::None => &mut self.items,
+                        };
+                        map.next_value_seed(DeserializeSeedNode2::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            items,
+                        })?;
+                    }
+                    FieldNode0::Field1 => {
+                        let mut next = core::option::Option::None;
+                        let next = match &mut self.next {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .next = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "next",
+                                            ".",
+                                            "duplicated field 'meta'",
+                                        ),
+                                    ),
+                                );
+                                &mut next
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut next
+                            }
+                            core::option::Option::None => &mut self.next,
+                        };
+                        map.next_value_seed(DeserializeSeedNode6::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            next,
+                        })?;
+                    }
+                    FieldNode0::Ignore => {
+                        map.next_value::<serde_query::__priv::serde::de::IgnoredAny>()?;
+                    }
+                }
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "data" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode2::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            items: &mut *self.items,
+                        },
+                        serde_query::__priv::VariantDeserializer::fields(variant),
+                    )?;
+                }
+                "meta" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode6::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            next: &mut *self.next,
+                        },
+                        serde_query::__priv::VariantDeserializer::fields(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "items",
+                                ".",
+                                serde_query::__priv::variant_mismatch("data", &name),
+                            ),
+                        ),
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "next",
+                                ".",
+                                serde_query::__priv::variant_mismatch("meta", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "data" {
+                if self.items.is_none() {
+                    *self
+                        .items = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "items",
+                                ".",
+                                serde_query::__priv::variant_mismatch("data", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "meta" {
+                if self.next.is_none() {
+                    *self
+                        .next = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "next",
+                                ".",
+                                serde_query::__priv::variant_mismatch("meta", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    enum FieldNode0 {
+        Field0,
+        Field1,
+        Ignore,
+    }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for FieldNode0 {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(FieldVisitorNode0)
+        }
+    }
+    struct FieldVisitorNode0;
+    impl<'de> serde_query::__priv::serde::de::Visitor<'de> for FieldVisitorNode0 {
+        type Value = FieldNode0;
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                f,
+                "one of the following fields: 'data', or 'meta'",
+            )
+        }
+        fn visit_str<E>(self, value: &str) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                "data" => core::result::Result::Ok(FieldNode0::Field0),
+                "meta" => core::result::Result::Ok(FieldNode0::Field1),
+                _ => core::result::Result::Ok(FieldNode0::Ignore),
+            }
+        }
+        fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                b"data" => core::result::Result::Ok(FieldNode0::Field0),
+                b"meta" => core::result::Result::Ok(FieldNode0::Field1),
+                _ => core::result::Result::Ok(FieldNode0::Ignore),
+            }
+        }
+    }
+    struct DeserializeSeedNode2<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode2<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let visitor = VisitorNode2::<'_, T> {
+                __marker: core::marker::PhantomData,
+                items: self.items,
+            };
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
+            if self.items.is_none() {
+                *self
+                    .items = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "items",
+                            ".data",
+                            "missing field 'items'",
+                        ),
+                    ),
+                );
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode2<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode2<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                formatter,
+                "one of the following fields: 'items'",
+            )
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            while let core::option::Option::Some(key) = map.next_key::<FieldNode2>()? {
+                match key {
+                    FieldNode2::Field0 => {
+                        let mut items = core::option::Option::None;
+                        let items = match &mut self.items {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .items = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "items",
+                                            ".data",
+                                            "duplicated field 'items'",
+                                        ),
+                                    ),
+                                );
+                                &mut items
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut items
+                            }
+                            core::option::Option::None => &mut self.items,
+                        };
+                        map.next_value_seed(DeserializeSeedNode3::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            items,
+                        })?;
+                    }
+                    FieldNode2::Ignore => {
+                        map.next_value::<serde_query::__priv::serde::de::IgnoredAny>()?;
+                    }
+                }
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "items" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode3::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            items: &mut *self.items,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "items",
+                                ".data",
+                                serde_query::__priv::variant_mismatch("items", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "items" {
+                if self.items.is_none() {
+                    *self
+                        .items = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "items",
+                                ".data",
+                                serde_query::__priv::variant_mismatch("items", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    enum FieldNode2 {
+        Field0,
+        Ignore,
+    }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for FieldNode2 {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(FieldVisitorNode2)
+        }
+    }
+    struct FieldVisitorNode2;
+    impl<'de> serde_query::__priv::serde::de::Visitor<'de> for FieldVisitorNode2 {
+        type Value = FieldNode2;
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(f, "one of the following fields: 'items'")
+        }
+        fn visit_str<E>(self, value: &str) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                "items" => core::result::Result::Ok(FieldNode2::Field0),
+                _ => core::result::Result::Ok(FieldNode2::Ignore),
+            }
+        }
+        fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                b"items" => core::result::Result::Ok(FieldNode2::Field0),
+                _ => core::result::Result::Ok(FieldNode2::Ignore),
+            }
+        }
+    }
+    struct DeserializeSeedNode3<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode3<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let mut items = core::result::Result::Ok(
+                <Vec<T> as serde_query::__priv::Container>::empty(),
+            );
+            let visitor = VisitorNode3::<'_, T> {
+                __marker: core::marker::PhantomData,
+                items: &mut items,
+            };
+            serde_query::__priv::deserialize_seq_or_map(deserializer, visitor)?;
+            *self.items = core::option::Option::Some(items);
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode3<'query, T: 'query> {
+        items: &'query mut core::result::Result<Vec<T>, serde_query::__priv::Error>,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode3<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(formatter, "a sequence or a map")
+        }
+        fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::SeqAccess<'de>,
+        {
+            if let core::option::Option::Some(additional) = seq.size_hint() {
+                <Vec<
+                    T,
+                > as serde_query::__priv::Container>::reserve(
+                    self.items.as_mut().unwrap(),
+                    additional,
+                );
+            }
+            loop {
+                let mut items = core::option::Option::None;
+                match seq
+                    .next_element_seed(DeserializeSeedNode4::<'_, T> {
+                        __marker: core::marker::PhantomData,
+                        items: &mut items,
+                    })?
+                {
+                    core::option::Option::None => break,
+                    core::option::Option::Some(()) => {
+                        match &mut self.items {
+                            core::result::Result::Ok(ref mut container) => {
+                                match items {
+                                    core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                        <Vec<
+                                            T,
+                                        > as serde_query::__priv::Container>::extend_one(
+                                            container,
+                                            v,
+                                        )
+                                    }
+                                    core::option::Option::Some(
+                                        core::result::Result::Err(e),
+                                    ) => {
+                                        *self.items = core::result::Result::Err(e);
+                                    }
+                                    core::option::Option::None => {}
+                                }
+                            }
+                            core::result::Result::Err(_) => {}
+                        }
+                    }
+                };
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            if let core::option::Option::Some(additional) = map.size_hint() {
+                <Vec<
+                    T,
+                > as serde_query::__priv::Container>::reserve(
+                    self.items.as_mut().unwrap(),
+                    additional,
+                );
+            }
+            while map.next_key::<serde_query::__priv::serde::de::IgnoredAny>()?.is_some()
+            {
+                let mut items = core::option::Option::None;
+                map.next_value_seed(DeserializeSeedNode4::<'_, T> {
+                    __marker: core::marker::PhantomData,
+                    items: &mut items,
+                })?;
+                match &mut self.items {
+                    core::result::Result::Ok(ref mut container) => {
+                        match items {
+                            core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                <Vec<
+                                    T,
+                                > as serde_query::__priv::Container>::extend_one(
+                                    container,
+                                    v,
+                                )
+                            }
+                            core::option::Option::Some(core::result::Result::Err(e)) => {
+                                *self.items = core::result::Result::Err(e);
+                            }
+                            core::option::Option::None => {}
+                        }
+                    }
+                    core::result::Result::Err(_) => {}
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct DeserializeSeedNode4<'query, T: 'query> {
+        items: &'query mut core::option::Option<
+            core::result::Result<
+                <Vec<T> as serde_query::__priv::Container>::Element,
+                serde_query::__priv::Error,
+            >,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode4<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(
+            self,
+            deserializer: D,
+        ) -> core::result::Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let result = match <<Vec<
+                T,
+            > as serde_query::__priv::Container>::Element as serde_query::__priv::serde::Deserialize<
+                'de,
+            >>::deserialize(deserializer) {
+                core::result::Result::Ok(v) => core::result::Result::Ok(v),
+                core::result::Result::Err(e) => {
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::owned(
+                            "items",
+                            ".data.items.[]",
+                            e.to_string(),
+                        ),
+                    )
+                }
+            };
+            *self.items = core::option::Option::Some(result);
+            core::result::Result::Ok(())
+        }
+    }
+    struct DeserializeSeedNode6<'query, T: 'query> {
+        next: &'query mut core::option::Option<
+            core::result::Result<Option<String>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode6<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let visitor = VisitorNode6::<'_, T> {
+                __marker: core::marker::PhantomData,
+                next: self.next,
+            };
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
+            if self.next.is_none() {
+                *self
+                    .next = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "next",
+                            ".meta",
+                            "missing field 'next'",
+                        ),
+                    ),
+                );
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode6<'query, T: 'query> {
+        next: &'query mut core::option::Option<
+            core::result::Result<Option<String>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode6<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                formatter,
+                "one of the following fields: 'next'",
+            )
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            while let core::option::Option::Some(key) = map.next_key::<FieldNode6>()? {
+                match key {
+                    FieldNode6::Field0 => {
+                        let mut next = core::option::Option::None;
+                        let next = match &mut self.next {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .next = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "next",
+                                            ".meta",
+                                            "duplicated field 'next'",
+                                        ),
+                                    ),
+                                );
+                                &mut next
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut next
+                            }
+                            core::option::Option::None => &mut self.next,
+                        };
+                        map.next_value_seed(DeserializeSeedNode7::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            next,
+                        })?;
+                    }
+                    FieldNode6::Ignore => {
+                        map.next_value::<serde_query::__priv::serde::de::IgnoredAny>()?;
+                    }
+                }
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "next" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode7::<'_, T> {
+                            __marker: core::marker::PhantomData,
+                            next: &mut *self.next,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "next",
+                                ".meta",
+                                serde_query::__priv::variant_mismatch("next", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "next" {
+                if self.next.is_none() {
+                    *self
+                        .next = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "next",
+                                ".meta",
+                                serde_query::__priv::variant_mismatch("next", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    enum FieldNode6 {
+        Field0,
+        Ignore,
+    }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for FieldNode6 {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(FieldVisitorNode6)
+        }
+    }
+    struct FieldVisitorNode6;
+    impl<'de> serde_query::__priv::serde::de::Visitor<'de> for FieldVisitorNode6 {
+        type Value = FieldNode6;
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(f, "one of the following fields: 'next'")
+        }
+        fn visit_str<E>(self, value: &str) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                "next" => core::result::Result::Ok(FieldNode6::Field0),
+                _ => core::result::Result::Ok(FieldNode6::Ignore),
+            }
+        }
+        fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                b"next" => core::result::Result::Ok(FieldNode6::Field0),
+                _ => core::result::Result::Ok(FieldNode6::Ignore),
+            }
+        }
+    }
+    struct DeserializeSeedNode7<'query, T: 'query> {
+        next: &'query mut core::option::Option<
+            core::result::Result<Option<String>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(fn() -> T,)>,
+    }
+    impl<'query, 'de, T: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode7<'query, T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        type Value = ();
+        fn deserialize<D>(
+            self,
+            deserializer: D,
+        ) -> core::result::Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let result = match <Option<
+                String,
+            > as serde_query::__priv::serde::Deserialize<
+                'de,
+            >>::deserialize(deserializer) {
+                core::result::Result::Ok(v) => core::result::Result::Ok(v),
+                core::result::Result::Err(e) => {
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::owned(
+                            "next",
+                            ".meta.next",
+                            e.to_string(),
+                        ),
+                    )
+                }
+            };
+            *self.next = core::option::Option::Some(result);
+            core::result::Result::Ok(())
+        }
+    }
+    impl<'de, T> serde_query::__priv::DeserializeNested<'de> for Page<T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        fn deserialize_nested<D>(
+            deserializer: D,
+        ) -> core::result::Result<
+            core::result::Result<Self, serde_query::__priv::Errors>,
+            D::Error,
+        >
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            let mut items = core::option::Option::None;
+            let mut next = core::option::Option::None;
+            let root = DeserializeSeedNode0::<'_, T> {
+                __marker: core::marker::PhantomData,
+                items: &mut items,
+                next: &mut next,
+            };
+            <DeserializeSeedNode0<
+                '_,
+                T,
+            > as serde_query::__priv::serde::de::DeserializeSeed<
+                'de,
+            >>::deserialize(root, deserializer)?;
+            let items = items.unwrap();
+            let next = next.unwrap();
+            let has_error = false || items.is_err() || next.is_err();
+            if !has_error {
+                let value = Page {
+                    items: items.unwrap(),
+                    next: next.unwrap(),
+                };
+                core::result::Result::Ok(core::result::Result::Ok(value))
+            } else {
+                let errors = [items.err(), next.err()];
+                core::result::Result::Ok(
+                    core::result::Result::Err(serde_query::__priv::Errors::new(errors)),
+                )
+            }
+        }
+    }
+    impl<'de, T> serde_query::__priv::serde::de::Deserialize<'de> for Page<T>
+    where
+        T: serde_query::__priv::serde::Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            match <Self as serde_query::__priv::DeserializeNested<
+                'de,
+            >>::deserialize_nested(deserializer)? {
+                core::result::Result::Ok(value) => core::result::Result::Ok(value),
+                core::result::Result::Err(errors) => {
+                    core::result::Result::Err(
+                        <D::Error as serde_query::__priv::serde::de::Error>::custom(
+                            errors,
+                        ),
+                    )
+                }
+            }
+        }
+    }
+};
+
+"#
+    );
+}
+
+#[test]
+fn test_borrow() {
+    let input: DeriveInput = syn::parse_quote! {
+        struct Row<'a> {
+            #[query(".name")]
+            name: &'a str,
+            #[query(".tags.[]")]
+            tags: Vec<Cow<'a, str>>,
+        }
+    };
+
+    k9::snapshot!(
+        snapshot_derive(input, DeriveTarget::Deserialize),
+        r#"
+const _: () = {
+    struct DeserializeSeedNode0<'query, 'a: 'query> {
+        name: &'query mut core::option::Option<
+            core::result::Result<&'a str, serde_query::__priv::Error>,
+        >,
+        tags: &'query mut core::option::Option<
+            core::result::Result<Vec<Cow<'a, str>>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode0<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let visitor = VisitorNode0::<'_, 'a> {
+                __marker: core::marker::PhantomData,
+                name: self.name,
+                tags: self.tags,
+            };
+            serde_query::__priv::deserialize_map_or_enum(deserializer, visitor)?;
+            if self.name.is_none() {
+                *self
+                    .name = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "name",
+                            ".",
+                            "missing field 'name'",
+                        ),
+                    ),
+                );
+            }
+            if self.tags.is_none() {
+                *self
+                    .tags = core::option::Option::Some(
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::borrowed(
+                            "tags",
+                            ".",
+                            "missing field 'tags'",
+                        ),
+                    ),
+                );
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode0<'query, 'a: 'query> {
+        name: &'query mut core::option::Option<
+            core::result::Result<&'a str, serde_query::__priv::Error>,
+        >,
+        tags: &'query mut core::option::Option<
+            core::result::Result<Vec<Cow<'a, str>>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode0<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                formatter,
+                "one of the following fields: 'name', or 'tags'",
+            )
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            while let core::option::Option::Some(key) = map.next_key::<FieldNode0>()? {
+                match key {
+                    FieldNode0::Field0 => {
+                        let mut name = core::option::Option::None;
+                        let name = match &mut self.name {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .name = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "name",
+                                            ".",
+                                            "duplicated field 'name'",
+                                        ),
+                                    ),
+                                );
+                                &mut name
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut name
+                            }
+                            core::option::Option::None => &mut self.name,
+                        };
+                        map.next_value_seed(DeserializeSeedNode2::<'_, 'a> {
+                            __marker: core::marker::PhantomData,
+                            name,
+                        })?;
+                    }
+                    FieldNode0::Field1 => {
+                        let mut tags = core::option::Option::None;
+                        let tags = match &mut self.tags {
+                            core::option::Option::Some(core::result::Result::Ok(_)) => {
+                                *self
+                                    .tags = core::option::Option::Some(
+                                    core::result::Result::Err(
+                                        serde_query::__priv::Error::borrowed(
+                                            "tags",
+                                            ".",
+                                            "duplicated field 'tags'",
+                                        ),
+                                    ),
+                                );
+                                &mut tags
+                            }
+                            core::option::Option::Some(core::result::Result::Err(_)) => {
+                                &mut tags
+                            }
+                            core::option::Option::None => &mut self.tags,
+                        };
+                        map.next_value_seed(DeserializeSeedNode4::<'_, 'a> {
+                            __marker: core::marker::PhantomData,
+                            tags,
+                        })?;
+                    }
+                    FieldNode0::Ignore => {
+                        map.next_value::<serde_query::__priv::serde::de::IgnoredAny>()?;
+                    }
+                }
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::EnumAccess<'de>,
+        {
+            let (name, variant) = data.variant::<serde_query::__priv::VariantName>()?;
+            match name.0.as_str() {
+                "name" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode2::<'_, 'a> {
+                            __marker: core::marker::PhantomData,
+                            name: &mut *self.name,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                "tags" => {
+                    serde_query::__priv::serde::de::DeserializeSeed::deserialize(
+                        DeserializeSeedNode4::<'_, 'a> {
+                            __marker: core::marker::PhantomData,
+                            tags: &mut *self.tags,
+                        },
+                        serde_query::__priv::VariantDeserializer::new(variant),
+                    )?;
+                }
+                _ => {
+                    let errors = [
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "name",
+                                ".",
+                                serde_query::__priv::variant_mismatch("name", &name),
+                            ),
+                        ),
+                        core::option::Option::Some(
+                            serde_query::__priv::Error::owned(
+                                "tags",
+                                ".",
+                                serde_query::__priv::variant_mismatch("tags", &name),
+                            ),
+                        ),
+                    ];
+                    return core::result::Result::Err(
+                        <A::Error as serde_query::__priv::serde::de::Error>::custom(
+                            serde_query::__priv::Errors::new(errors),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "name" {
+                if self.name.is_none() {
+                    *self
+                        .name = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "name",
+                                ".",
+                                serde_query::__priv::variant_mismatch("name", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            if name.0 != "tags" {
+                if self.tags.is_none() {
+                    *self
+                        .tags = core::option::Option::Some(
+                        core::result::Result::Err(
+                            serde_query::__priv::Error::owned(
+                                "tags",
+                                ".",
+                                serde_query::__priv::variant_mismatch("tags", &name),
+                            ),
+                        ),
+                    );
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    enum FieldNode0 {
+        Field0,
+        Field1,
+        Ignore,
+    }
+    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for FieldNode0 {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(FieldVisitorNode0)
+        }
+    }
+    struct FieldVisitorNode0;
+    impl<'de> serde_query::__priv::serde::de::Visitor<'de> for FieldVisitorNode0 {
+        type Value = FieldNode0;
+        fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(
+                f,
+                "one of the following fields: 'name', or 'tags'",
+            )
+        }
+        fn visit_str<E>(self, value: &str) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                "name" => core::result::Result::Ok(FieldNode0::Field0),
+                "tags" => core::result::Result::Ok(FieldNode0::Field1),
+                _ => core::result::Result::Ok(FieldNode0::Ignore),
+            }
+        }
+        fn visit_bytes<E>(self, value: &[u8]) -> core::result::Result<Self::Value, E>
+        where
+            E: serde_query::__priv::serde::de::Error,
+        {
+            match value {
+                b"name" => core::result::Result::Ok(FieldNode0::Field0),
+                b"tags" => core::result::Result::Ok(FieldNode0::Field1),
+                _ => core::result::Result::Ok(FieldNode0::Ignore),
+            }
+        }
+    }
+    struct DeserializeSeedNode2<'query, 'a: 'query> {
+        name: &'query mut core::option::Option<
+            core::result::Result<&'a str, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode2<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn deserialize<D>(
+            self,
+            deserializer: D,
+        ) -> core::result::Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let result = match <&'a str as serde_query::__priv::serde::Deserialize<
+                'de,
+            >>::deserialize(deserializer) {
+                core::result::Result::Ok(v) => core::result::Result::Ok(v),
+                core::result::Result::Err(e) => {
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::owned("name", ".name", e.to_string()),
+                    )
+                }
+            };
+            *self.name = core::option::Option::Some(result);
+            core::result::Result::Ok(())
+        }
+    }
+    struct DeserializeSeedNode4<'query, 'a: 'query> {
+        tags: &'query mut core::option::Option<
+            core::result::Result<Vec<Cow<'a, str>>, serde_query::__priv::Error>,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode4<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let mut tags = core::result::Result::Ok(
+                <Vec<Cow<'a, str>> as serde_query::__priv::Container>::empty(),
+            );
+            let visitor = VisitorNode4::<'_, 'a> {
+                __marker: core::marker::PhantomData,
+                tags: &mut tags,
+            };
+            serde_query::__priv::deserialize_seq_or_map(deserializer, visitor)?;
+            *self.tags = core::option::Option::Some(tags);
+            core::result::Result::Ok(())
+        }
+    }
+    struct VisitorNode4<'query, 'a: 'query> {
+        tags: &'query mut core::result::Result<
+            Vec<Cow<'a, str>>,
+            serde_query::__priv::Error,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::Visitor<'de>
+    for VisitorNode4<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Formatter::write_str(formatter, "a sequence or a map")
+        }
+        fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::SeqAccess<'de>,
+        {
+            if let core::option::Option::Some(additional) = seq.size_hint() {
+                <Vec<
+                    Cow<'a, str>,
+                > as serde_query::__priv::Container>::reserve(
+                    self.tags.as_mut().unwrap(),
+                    additional,
+                );
+            }
+            loop {
+                let mut tags = core::option::Option::None;
+                match seq
+                    .next_element_seed(DeserializeSeedNode5::<'_, 'a> {
+                        __marker: core::marker::PhantomData,
+                        tags: &mut tags,
+                    })?
+                {
+                    core::option::Option::None => break,
+                    core::option::Option::Some(()) => {
+                        match &mut self.tags {
+                            core::result::Result::Ok(ref mut container) => {
+                                match tags {
+                                    core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                        <Vec<
+                                            Cow<'a, str>,
+                                        > as serde_query::__priv::Container>::extend_one(
+                                            container,
+                                            v,
+                                        )
+                                    }
+                                    core::option::Option::Some(
+                                        core::result::Result::Err(e),
+                                    ) => {
+                                        *self.tags = core::result::Result::Err(e);
+                                    }
+                                    core::option::Option::None => {}
+                                }
+                            }
+                            core::result::Result::Err(_) => {}
+                        }
+                    }
+                };
+            }
+            core::result::Result::Ok(())
+        }
+        fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde_query::__priv::serde::de::MapAccess<'de>,
+        {
+            if let core::option::Option::Some(additional) = map.size_hint() {
+                <Vec<
+                    Cow<'a, str>,
+                > as serde_query::__priv::Container>::reserve(
+                    self.tags.as_mut().unwrap(),
+                    additional,
+                );
+            }
+            while map.next_key::<serde_query::__priv::serde::de::IgnoredAny>()?.is_some()
+            {
+                let mut tags = core::option::Option::None;
+                map.next_value_seed(DeserializeSeedNode5::<'_, 'a> {
+                    __marker: core::marker::PhantomData,
+                    tags: &mut tags,
+                })?;
+                match &mut self.tags {
+                    core::result::Result::Ok(ref mut container) => {
+                        match tags {
+                            core::option::Option::Some(core::result::Result::Ok(v)) => {
+                                <Vec<
+                                    Cow<'a, str>,
+                                > as serde_query::__priv::Container>::extend_one(
+                                    container,
+                                    v,
+                                )
+                            }
+                            core::option::Option::Some(core::result::Result::Err(e)) => {
+                                *self.tags = core::result::Result::Err(e);
+                            }
+                            core::option::Option::None => {}
+                        }
+                    }
+                    core::result::Result::Err(_) => {}
+                }
+            }
+            core::result::Result::Ok(())
+        }
+    }
+    struct DeserializeSeedNode5<'query, 'a: 'query> {
+        tags: &'query mut core::option::Option<
+            core::result::Result<
+                <Vec<Cow<'a, str>> as serde_query::__priv::Container>::Element,
+                serde_query::__priv::Error,
+            >,
+        >,
+        __marker: core::marker::PhantomData<(&'a (),)>,
+    }
+    impl<'query, 'de, 'a: 'query> serde_query::__priv::serde::de::DeserializeSeed<'de>
+    for DeserializeSeedNode5<'query, 'a>
+    where
+        'de: 'a,
+    {
+        type Value = ();
+        fn deserialize<D>(
+            self,
+            deserializer: D,
+        ) -> core::result::Result<Self::Value, D::Error>
+        where
+            D: serde_query::__priv::serde::Deserializer<'de>,
+        {
+            let result = match <<Vec<
+                Cow<'a, str>,
+            > as serde_query::__priv::Container>::Element as serde_query::__priv::DeserializeBorrowed<
+                'de,
+            >>::deserialize_borrowed(deserializer) {
+                core::result::Result::Ok(v) => core::result::Result::Ok(v),
+                core::result::Result::Err(e) => {
+                    core::result::Result::Err(
+                        serde_query::__priv::Error::owned(
+                            "tags",
+                            ".tags.[]",
+                            e.to_string(),
+                        ),
+                    )
+                }
+            };
+            *self.tags = core::option::Option::Some(result);
+            core::result::Result::Ok(())
+        }
+    }
+    impl<'de, 'a> serde_query::__priv::DeserializeNested<'de> for Row<'a>
+    where
+        'de: 'a,
+    {
+        fn deserialize_nested<D>(
+            deserializer: D,
+        ) -> core::result::Result<
+            core::result::Result<Self, serde_query::__priv::Errors>,
+            D::Error,
+        >
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            let mut name = core::option::Option::None;
+            let mut tags = core::option::Option::None;
+            let root = DeserializeSeedNode0::<'_, 'a> {
+                __marker: core::marker::PhantomData,
+                name: &mut name,
+                tags: &mut tags,
+            };
+            <DeserializeSeedNode0<
+                '_,
+                'a,
+            > as serde_query::__priv::serde::de::DeserializeSeed<
+                'de,
+            >>::deserialize(root, deserializer)?;
+            let name = name.unwrap();
+            let tags = tags.unwrap();
+            let has_error = false || name.is_err() || tags.is_err();
+            if !has_error {
+                let value = Row {
+                    name: name.unwrap(),
+                    tags: tags.unwrap(),
+                };
+                core::result::Result::Ok(core::result::Result::Ok(value))
+            } else {
+                let errors = [name.err(), tags.err()];
+                core::result::Result::Ok(
+                    core::result::Result::Err(serde_query::__priv::Errors::new(errors)),
+                )
+            }
+        }
+    }
+    impl<'de, 'a> serde_query::__priv::serde::de::Deserialize<'de> for Row<'a>
+    where
+        'de: 'a,
+    {
+        fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+        where
+            D: serde_query::__priv::serde::de::Deserializer<'de>,
+        {
+            match <Self as serde_query::__priv::DeserializeNested<
+                'de,
+            >>::deserialize_nested(deserializer)? {
+                core::result::Result::Ok(value) => core::result::Result::Ok(value),
+                core::result::Result::Err(errors) => {
+                    core::result::Result::Err(
+                        <D::Error as serde_query::__priv::serde::de::Error>::custom(
+                            errors,
+                        ),
+                    )
+                }
+            }
+        }
+    }
+};
+
+"#
+    );
+}
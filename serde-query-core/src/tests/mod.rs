@@ -2,9 +2,9 @@ use syn::DeriveInput;
 
 use crate::{generate_derive, DeriveTarget};
 
-mod conflicting_accept;
 mod empty;
 mod generate_basic;
+mod generate_generic;
 
 fn snapshot_derive(input: DeriveInput, target: DeriveTarget) -> String {
     match generate_derive(input, target) {
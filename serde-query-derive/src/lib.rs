@@ -1,7 +1,7 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use proc_macro_error::{emit_error, proc_macro_error};
+use proc_macro_error::proc_macro_error;
 use quote::quote;
 use serde_query_core::DeriveTarget;
 use syn::{parse_macro_input, DeriveInput};
@@ -9,13 +9,16 @@ use syn::{parse_macro_input, DeriveInput};
 /// Generate a minimal, non-functioning Deserialize(Query) implementation on errors
 fn set_dummy(input: &DeriveInput, target: DeriveTarget) {
     let name = &input.ident;
-    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut de_generics = input.generics.clone();
+    de_generics.params.insert(0, syn::parse_quote!('de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
 
     match target {
         DeriveTarget::Deserialize => {
             proc_macro_error::set_dummy(quote! {
                 const _: () = {
-                    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for #name #generics {
+                    impl #de_impl_generics serde_query::__priv::serde::de::Deserialize<'de> for #name #ty_generics #where_clause {
                         fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
                         where
                             D: serde_query::__priv::serde::de::Deserializer<'de>
@@ -29,9 +32,9 @@ fn set_dummy(input: &DeriveInput, target: DeriveTarget) {
         DeriveTarget::DeserializeQuery => {
             proc_macro_error::set_dummy(quote! {
                 const _: () = {
-                    struct __QueryWrapper;
+                    struct __QueryWrapper #impl_generics (#name #ty_generics) #where_clause;
 
-                    impl<'de> serde_query::__priv::serde::de::Deserialize<'de> for __QueryWrapper {
+                    impl #de_impl_generics serde_query::__priv::serde::de::Deserialize<'de> for __QueryWrapper #ty_generics #where_clause {
                         fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
                         where
                             D: serde_query::__priv::serde::de::Deserializer<'de>
@@ -40,28 +43,28 @@ fn set_dummy(input: &DeriveInput, target: DeriveTarget) {
                         }
                     }
 
-                    impl core::convert::From<__QueryWrapper> for #name #generics {
-                        fn from(val: __QueryWrapper) -> Self {
+                    impl #impl_generics core::convert::From<__QueryWrapper #ty_generics> for #name #ty_generics #where_clause {
+                        fn from(val: __QueryWrapper #ty_generics) -> Self {
                             todo!()
                         }
                     }
 
-                    impl core::ops::Deref for __QueryWrapper {
-                        type Target = #name;
+                    impl #impl_generics core::ops::Deref for __QueryWrapper #ty_generics #where_clause {
+                        type Target = #name #ty_generics;
 
                         fn deref(&self) -> &Self::Target {
                             todo!()
                         }
                     }
 
-                    impl core::ops::DerefMut for __QueryWrapper {
+                    impl #impl_generics core::ops::DerefMut for __QueryWrapper #ty_generics #where_clause {
                         fn deref_mut(&mut self) -> &mut Self::Target {
                             todo!()
                         }
                     }
 
-                    impl<'de> serde_query::DeserializeQuery<'de> for #name #generics {
-                        type Query = __QueryWrapper;
+                    impl #de_impl_generics serde_query::DeserializeQuery<'de> for #name #ty_generics #where_clause {
+                        type Query = __QueryWrapper #ty_generics;
                     }
                 };
             });
@@ -74,11 +77,6 @@ fn generate_derive(input: TokenStream, target: DeriveTarget) -> TokenStream {
 
     set_dummy(&input, target);
 
-    if !input.generics.params.is_empty() {
-        emit_error!(input.generics, "generic arguments are not supported");
-        return TokenStream::new();
-    }
-
     match serde_query_core::generate_derive(input, target) {
         Ok(stream) => stream.into(),
         Err(diagnostics) => {
@@ -0,0 +1,201 @@
+//! Zero-copy deserialization of `Cow`.
+//!
+//! `Cow<'a, str>` always deserializes into an owned string. The generated code uses this trait
+//! instead for the fields of type `Cow<'a, str>` or `Cow<'a, [u8]>` with a lifetime of the struct,
+//! optionally inside `Option` or `Vec`, as `#[serde(borrow)]` does.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+
+pub trait DeserializeBorrowed<'de>: Sized {
+    fn deserialize_borrowed<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+struct CowStrVisitor;
+
+impl<'a> Visitor<'a> for CowStrVisitor {
+    type Value = Cow<'a, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'a str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'a [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match core::str::from_utf8(v) {
+            Ok(s) => Ok(Cow::Borrowed(s)),
+            Err(_) => Err(de::Error::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match core::str::from_utf8(v) {
+            Ok(s) => Ok(Cow::Owned(s.into())),
+            Err(_) => Err(de::Error::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> DeserializeBorrowed<'de> for Cow<'a, str> {
+    fn deserialize_borrowed<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
+
+struct CowBytesVisitor;
+
+impl<'a> Visitor<'a> for CowBytesVisitor {
+    type Value = Cow<'a, [u8]>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'a str) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v.as_bytes()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.as_bytes().into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.into_bytes()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'a [u8]) -> Result<Self::Value, E> {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v.into()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Cow::Owned(v))
+    }
+}
+
+impl<'de: 'a, 'a> DeserializeBorrowed<'de> for Cow<'a, [u8]> {
+    fn deserialize_borrowed<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(CowBytesVisitor)
+    }
+}
+
+struct OptionVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T> Visitor<'de> for OptionVisitor<T>
+where
+    T: DeserializeBorrowed<'de>,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("option")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_borrowed(deserializer).map(Some)
+    }
+}
+
+impl<'de, T> DeserializeBorrowed<'de> for Option<T>
+where
+    T: DeserializeBorrowed<'de>,
+{
+    fn deserialize_borrowed<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(OptionVisitor(PhantomData))
+    }
+}
+
+/// Deserializes an element with [`DeserializeBorrowed`].
+struct Element<T>(T);
+
+impl<'de, T> de::Deserialize<'de> for Element<T>
+where
+    T: DeserializeBorrowed<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_borrowed(deserializer).map(Element)
+    }
+}
+
+struct VecVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T> Visitor<'de> for VecVisitor<T>
+where
+    T: DeserializeBorrowed<'de>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(Element(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl<'de, T> DeserializeBorrowed<'de> for Vec<T>
+where
+    T: DeserializeBorrowed<'de>,
+{
+    fn deserialize_borrowed<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(VecVisitor(PhantomData))
+    }
+}
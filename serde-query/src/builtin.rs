@@ -0,0 +1,299 @@
+//! Built-in functions (`length`, `keys`, `has`, and `type`).
+//!
+//! The functions at the same value are computed in a single pass. The elements are skipped
+//! without being deserialized, and only the keys are buffered when `keys` is requested.
+
+use alloc::{format, string::ToString, vec::Vec};
+use core::{cmp::Ordering, convert::TryFrom, fmt};
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, Deserialize, DeserializeSeed, Deserializer, IgnoredAny,
+    IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+
+use crate::{
+    __priv::{Container, Error},
+    content::{Content, ContentDeserializer},
+};
+
+#[derive(Clone, Copy)]
+enum Type {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Type {
+    fn as_str(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+}
+
+/// Computes the built-in functions for a value.
+pub struct BuiltinSeed {
+    /// Whether to collect the keys.
+    pub keys: bool,
+    /// The keys to look for.
+    pub has: &'static [&'static str],
+}
+
+/// The results of the built-in functions for a value.
+pub struct Builtins<'de> {
+    ty: Type,
+    length: usize,
+    /// The absolute value of a number, which is its `length` in jq.
+    absolute: Option<Content<'de>>,
+    keys: Vec<Content<'de>>,
+    has: Vec<bool>,
+}
+
+impl<'de> Builtins<'de> {
+    fn new(ty: Type, seed: &BuiltinSeed) -> Self {
+        Self {
+            ty,
+            length: 0,
+            absolute: None,
+            keys: Vec::new(),
+            has: alloc::vec![false; seed.has.len()],
+        }
+    }
+
+    fn error(&self, field: &'static str, prefix: &'static str, function: &str) -> Error {
+        Error::owned(
+            field,
+            prefix,
+            format!("cannot compute '{}' of {}", function, self.ty.as_str()),
+        )
+    }
+
+    fn convert<T, D>(field: &'static str, prefix: &'static str, deserializer: D) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de, Error = de::value::Error>,
+    {
+        T::deserialize(deserializer).map_err(|e| Error::owned(field, prefix, e.to_string()))
+    }
+
+    pub fn length<T>(&self, field: &'static str, prefix: &'static str) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        match (self.ty, &self.absolute) {
+            (Type::Number, Some(absolute)) => {
+                Self::convert(field, prefix, ContentDeserializer::new(absolute.clone()))
+            }
+            (Type::Boolean | Type::Number, _) => Err(self.error(field, prefix, "length")),
+            _ => Self::convert(field, prefix, self.length.into_deserializer()),
+        }
+    }
+
+    pub fn keys<T>(&self, field: &'static str, prefix: &'static str) -> Result<T, Error>
+    where
+        T: Container,
+        T::Element: Deserialize<'de>,
+    {
+        match self.ty {
+            Type::Array | Type::Object => {
+                let mut container = T::empty();
+                container.reserve(self.keys.len());
+                for key in self.keys.iter() {
+                    container.extend_one(Self::convert(
+                        field,
+                        prefix,
+                        ContentDeserializer::new(key.clone()),
+                    )?);
+                }
+                Ok(container)
+            }
+            _ => Err(self.error(field, prefix, "keys")),
+        }
+    }
+
+    pub fn has<T>(
+        &self,
+        index: usize,
+        field: &'static str,
+        prefix: &'static str,
+    ) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        match self.ty {
+            Type::Object => Self::convert(field, prefix, self.has[index].into_deserializer()),
+            _ => Err(self.error(field, prefix, "has")),
+        }
+    }
+
+    pub fn type_name<T>(&self, field: &'static str, prefix: &'static str) -> Result<T, Error>
+    where
+        T: Deserialize<'de>,
+    {
+        Self::convert(
+            field,
+            prefix,
+            BorrowedStrDeserializer::new(self.ty.as_str()),
+        )
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for BuiltinSeed {
+    type Value = Builtins<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for BuiltinSeed {
+    type Value = Builtins<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+        Ok(Builtins::new(Type::Boolean, &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        let mut builtins = Builtins::new(Type::Number, &self);
+        builtins.absolute = Some(Content::U64(v.unsigned_abs()));
+        Ok(builtins)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        let mut builtins = Builtins::new(Type::Number, &self);
+        builtins.absolute = Some(Content::U64(v));
+        Ok(builtins)
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        let mut builtins = Builtins::new(Type::Number, &self);
+        builtins.absolute = Some(Content::F64(v.abs()));
+        Ok(builtins)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        let mut builtins = Builtins::new(Type::String, &self);
+        builtins.length = v.chars().count();
+        Ok(builtins)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let mut builtins = Builtins::new(Type::Array, &self);
+        builtins.length = v.len();
+        if self.keys {
+            builtins.keys = (0..v.len() as u64).map(Content::U64).collect();
+        }
+        Ok(builtins)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Builtins::new(Type::Null, &self))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(Builtins::new(Type::Null, &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut builtins = Builtins::new(Type::Array, &self);
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            if self.keys {
+                builtins.keys.push(Content::U64(builtins.length as u64));
+            }
+            builtins.length += 1;
+        }
+        Ok(builtins)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut builtins = Builtins::new(Type::Object, &self);
+        if !self.keys && self.has.is_empty() {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {
+                builtins.length += 1;
+            }
+            return Ok(builtins);
+        }
+
+        while let Some(key) = map.next_key::<Content<'de>>()? {
+            map.next_value::<IgnoredAny>()?;
+            let name = match &key {
+                Content::String(name) => Some(name.as_str()),
+                Content::Str(name) => Some(*name),
+                _ => None,
+            };
+            for (has, expected) in builtins.has.iter_mut().zip(self.has) {
+                *has |= name == Some(*expected);
+            }
+            if self.keys {
+                builtins.keys.push(key);
+            }
+            builtins.length += 1;
+        }
+        // jq sorts the keys
+        builtins.keys.sort_by(compare_keys);
+        Ok(builtins)
+    }
+}
+
+/// Orders the keys of a map as jq does: numbers before strings, and strings by their code points.
+fn compare_keys(a: &Content, b: &Content) -> Ordering {
+    fn as_str<'a>(key: &'a Content) -> Option<&'a str> {
+        match key {
+            Content::String(key) => Some(key.as_str()),
+            Content::Str(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    fn as_integer(key: &Content) -> Option<i128> {
+        match *key {
+            Content::U64(key) => Some(key.into()),
+            Content::I64(key) => Some(key.into()),
+            Content::U128(key) => i128::try_from(key).ok(),
+            Content::I128(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    match (as_str(a), as_str(b)) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => as_integer(a).cmp(&as_integer(b)),
+    }
+}
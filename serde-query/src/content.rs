@@ -0,0 +1,529 @@
+//! A buffered representation of the Serde data model.
+//!
+//! This is a stripped-down version of serde's private `Content` type. The generated code uses it
+//! when the query cannot be answered in a single pass over the input, e.g., for `.[-1]`.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::marker::PhantomData;
+
+use serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+    Deserialize, Deserializer, IntoDeserializer, Visitor,
+};
+
+#[derive(Debug, Clone)]
+pub enum Content<'de> {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    F64(f64),
+    Char(char),
+    String(String),
+    Str(&'de str),
+    ByteBuf(Vec<u8>),
+    Bytes(&'de [u8]),
+    None,
+    Some(Box<Content<'de>>),
+    Unit,
+    Newtype(Box<Content<'de>>),
+    Seq(Vec<Content<'de>>),
+    Map(Vec<(Content<'de>, Content<'de>)>),
+}
+
+impl<'de> Deserialize<'de> for Content<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl<'de> Visitor<'de> for ContentVisitor {
+    type Value = Content<'de>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Content::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Content::I64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Content::U64(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(Content::I128(v))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(Content::U128(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Content::F64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Self::Value, E> {
+        Ok(Content::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Content::String(v.into()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Content::Str(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Content::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Content::ByteBuf(v.into()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(Content::Bytes(v))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Content::ByteBuf(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Content::None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Content::deserialize(deserializer).map(|v| Content::Some(Box::new(v)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(Content::Unit)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Content::deserialize(deserializer).map(|v| Content::Newtype(Box::new(v)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Content::Seq(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Content::Map(entries))
+    }
+
+    // Enums are buffered in the externally tagged form, i.e., as a map with a single entry, so
+    // that `deserialize_enum` can replay them. Self-describing formats only hand out enums for
+    // tagged values like YAML's `!Tag value`, whose payload is a newtype.
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        use de::VariantAccess;
+
+        let (variant, value) = data.variant::<Content>()?;
+        let value = value.newtype_variant::<Content>()?;
+        Ok(Content::Map(alloc::vec![(variant, value)]))
+    }
+}
+
+impl<'de> Content<'de> {
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match self {
+            Content::Bool(v) => de::Unexpected::Bool(*v),
+            Content::U64(v) => de::Unexpected::Unsigned(*v),
+            Content::I64(v) => de::Unexpected::Signed(*v),
+            Content::U128(_) | Content::I128(_) => de::Unexpected::Other("128-bit integer"),
+            Content::F64(v) => de::Unexpected::Float(*v),
+            Content::Char(v) => de::Unexpected::Char(*v),
+            Content::String(v) => de::Unexpected::Str(v),
+            Content::Str(v) => de::Unexpected::Str(v),
+            Content::ByteBuf(v) => de::Unexpected::Bytes(v),
+            Content::Bytes(v) => de::Unexpected::Bytes(v),
+            Content::None | Content::Some(_) => de::Unexpected::Option,
+            Content::Unit => de::Unexpected::Unit,
+            Content::Newtype(_) => de::Unexpected::NewtypeStruct,
+            Content::Seq(_) => de::Unexpected::Seq,
+            Content::Map(_) => de::Unexpected::Map,
+        }
+    }
+}
+
+impl<'de, E> IntoDeserializer<'de, E> for Content<'de>
+where
+    E: de::Error,
+{
+    type Deserializer = ContentDeserializer<'de, E>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ContentDeserializer::new(self)
+    }
+}
+
+/// A deserializer that replays a buffered [`Content`].
+pub struct ContentDeserializer<'de, E> {
+    content: Content<'de>,
+    err: PhantomData<E>,
+}
+
+impl<'de, E> ContentDeserializer<'de, E> {
+    pub fn new(content: Content<'de>) -> Self {
+        ContentDeserializer {
+            content,
+            err: PhantomData,
+        }
+    }
+}
+
+impl<'de, E> Deserializer<'de> for ContentDeserializer<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Content::Bool(v) => visitor.visit_bool(v),
+            Content::U64(v) => visitor.visit_u64(v),
+            Content::I64(v) => visitor.visit_i64(v),
+            Content::U128(v) => visitor.visit_u128(v),
+            Content::I128(v) => visitor.visit_i128(v),
+            Content::F64(v) => visitor.visit_f64(v),
+            Content::Char(v) => visitor.visit_char(v),
+            Content::String(v) => visitor.visit_string(v),
+            Content::Str(v) => visitor.visit_borrowed_str(v),
+            Content::ByteBuf(v) => visitor.visit_byte_buf(v),
+            Content::Bytes(v) => visitor.visit_borrowed_bytes(v),
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+            Content::Unit => visitor.visit_unit(),
+            Content::Newtype(v) => visitor.visit_newtype_struct(ContentDeserializer::new(*v)),
+            Content::Seq(v) => {
+                let mut seq = SeqDeserializer::new(v.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Content::Map(v) => {
+                let mut map = MapDeserializer::new(v.into_iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+            Content::Unit => visitor.visit_unit(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.content {
+            Content::Newtype(v) => visitor.visit_newtype_struct(ContentDeserializer::new(*v)),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (variant, value) = match self.content {
+            Content::Map(entries) => {
+                let mut entries = entries.into_iter();
+                let (variant, value) = match entries.next() {
+                    Some(entry) => entry,
+                    None => {
+                        return Err(de::Error::invalid_value(
+                            de::Unexpected::Map,
+                            &"map with a single key",
+                        ))
+                    }
+                };
+                if entries.next().is_some() {
+                    return Err(de::Error::invalid_value(
+                        de::Unexpected::Map,
+                        &"map with a single key",
+                    ));
+                }
+                (variant, Some(value))
+            }
+            content @ (Content::String(_) | Content::Str(_)) => (content, None),
+            other => {
+                return Err(de::Error::invalid_type(
+                    other.unexpected(),
+                    &"string or map",
+                ))
+            }
+        };
+
+        visitor.visit_enum(EnumDeserializer {
+            variant,
+            value,
+            err: PhantomData,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// A deserializer that replays a buffered map key.
+///
+/// As with the map keys of `serde_json`, a string key deserializes into an integer if it parses as
+/// one, e.g., `{"1": "a"}` into `HashMap<u32, String>`.
+pub struct MapKeyDeserializer<'de, E> {
+    content: Content<'de>,
+    err: PhantomData<E>,
+}
+
+impl<'de, E> MapKeyDeserializer<'de, E> {
+    pub fn new(content: Content<'de>) -> Self {
+        MapKeyDeserializer {
+            content,
+            err: PhantomData,
+        }
+    }
+}
+
+macro_rules! deserialize_integer_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let parsed = match &self.content {
+                    Content::String(v) => v.parse().ok(),
+                    Content::Str(v) => v.parse().ok(),
+                    _ => None,
+                };
+                match parsed {
+                    Some(v) => visitor.$visit(v),
+                    None => ContentDeserializer::new(self.content).$method(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, E> Deserializer<'de> for MapKeyDeserializer<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ContentDeserializer::new(self.content).deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ContentDeserializer::new(self.content).deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ContentDeserializer::new(self.content).deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_integer_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer<'de, E> {
+    variant: Content<'de>,
+    value: Option<Content<'de>>,
+    err: PhantomData<E>,
+}
+
+impl<'de, E> de::EnumAccess<'de> for EnumDeserializer<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    type Variant = VariantDeserializer<'de, E>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(ContentDeserializer::new(self.variant))?;
+        Ok((
+            variant,
+            VariantDeserializer {
+                value: self.value,
+                err: PhantomData,
+            },
+        ))
+    }
+}
+
+struct VariantDeserializer<'de, E> {
+    value: Option<Content<'de>>,
+    err: PhantomData<E>,
+}
+
+impl<'de, E> de::VariantAccess<'de> for VariantDeserializer<'de, E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            Some(value) => Deserialize::deserialize(ContentDeserializer::new(value)),
+            None => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            Some(value) => seed.deserialize(ContentDeserializer::new(value)),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(Content::Seq(v)) => {
+                ContentDeserializer::new(Content::Seq(v)).deserialize_any(visitor)
+            }
+            Some(other) => Err(de::Error::invalid_type(
+                other.unexpected(),
+                &"tuple variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Some(Content::Map(v)) => {
+                ContentDeserializer::new(Content::Map(v)).deserialize_any(visitor)
+            }
+            Some(Content::Seq(v)) => {
+                ContentDeserializer::new(Content::Seq(v)).deserialize_any(visitor)
+            }
+            Some(other) => Err(de::Error::invalid_type(
+                other.unexpected(),
+                &"struct variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -58,17 +58,152 @@
 //!
 //! serde-query let you write a jq-like query inside the `#[query("...")]` annotation.
 //! Note that every field must have a query annotation.
+//! Tuple structs are supported as well, e.g. `struct Point(#[query(".x")] f64, #[query(".y")] f64);`.
+//! Their fields are referred to as `0`, `1`, and so on in error messages.
+//! Several fields can share the same query, e.g. `.version` for both the raw `String` and a parsed version.
+//! The value is buffered and deserialized for each field, so this requires a self-describing format such as JSON.
+//!
+//! With `#[query(implicit)]` on the struct, a field without the annotation is queried with `.<field name>`.
+//! You can rename the fields with `#[query(implicit, rename_all = "...")]` using the case conventions of serde: `"lowercase"`, `"UPPERCASE"`, `"PascalCase"`, `"camelCase"`, `"snake_case"`, `"SCREAMING_SNAKE_CASE"`, `"kebab-case"`, or `"SCREAMING-KEBAB-CASE"`.
+//! For example, `html_url: String` is queried with `.htmlUrl` under `rename_all = "camelCase"`.
+//!
+//! `#[query(root = "...")]` on a struct or an enum prepends a path of fields and indices to every query.
+//! For example, under `#[query(root = ".data.attributes")]`, `.title` is queried with `.data.attributes.title`.
+//! The error messages show the full path from the document root, and the tag of an enum is looked up from the document root as well.
+//!
+//! With `#[query("...", default)]` on a field, the field takes `Default::default()` if a field or an element on the path is missing.
+//! `#[query("...", default = "path::to::function")]` calls the function instead, e.g. `#[query(".server.timeout", default = "default_timeout")]`.
+//! Other errors such as type mismatches are still reported.
+//! The default is for the whole field, so a missing value inside `.[]` and the other collecting queries is still an error.
+//! With alternatives separated by `//`, the field takes the default if the last alternative is missing.
+//!
+//! `#[query("...", deserialize_with = "path::to::function")]` deserializes the value with the function instead of `Deserialize`, as in serde.
+//! `#[query("...", with = "module")]` uses `module::deserialize`.
+//! After `.[]` and the other collecting queries, the function deserializes each element, e.g. `#[query(".events[].time", with = "rfc3339")]` on `Vec<OffsetDateTime>`.
+//!
+//! `#[query("...", nested)]` mounts another type that derives `Deserialize` or `DeserializeQuery` of serde-query at the path, e.g. `#[query(".commit", nested)] commit: CommitInfo`.
+//! The queries of the nested type are relative to the path, and run while the document is traversed once.
+//! The errors show the full paths from the document root, such as `Query for field 'commit.author' failed at '.commit.author'`.
+//! After `.[]`, each element is deserialized as the nested type.
+//! Note that the other queries of the parent cannot look into the value at the path (e.g., `.commit.sha` next to `#[query(".commit", nested)]`), because the nested type takes the whole value.
+//! Such queries are rejected at compile time. Move them into the nested type instead.
 //!
 //! The supported syntaxes are as follows:
 //!
 //! * **`.field` syntax:** You can use the `.field` syntax to extract a field from a struct.
 //!   For example, `.name` extracts the `name` field. If the field name contains special characters, you can use the `.["field"]` syntax to quote the field name.
-//!   For example, `.["first-name"]` extracts the `first-name` field.
+//!   For example, `.["first-name"]` or `."first-name"` extracts the `first-name` field.
 //!   When quoting a field name, try using a raw string literal (i.e., `#[query(r#"..."#)]`).
+//!   Quoted field names support the JSON escape sequences such as `\n`, `\"`, and `\u00e9`.
+//! * **Enum variants:** The `.field` syntax steps into an enum variant of the same name in human-readable formats.
+//!   For example, `.event.Push.ref` extracts `ref` from `{"event": {"Push": {"ref": ...}}}` in JSON or `event: !Push { ref: ... }` in YAML.
+//!   Formats that tell variants apart only when an enum is expected, such as RON and bincode, need the `!Variant` syntax instead.
+//!   For example, `.event!Push.ref` extracts `ref` from `(event: Push(ref: ...))` in RON as well.
+//!   If the value is another variant, the error names the actual variant before its payload is read.
+//!   If every query at the variant is optional or has a fallback, the payload of another variant is skipped as a newtype variant, as serde's `IgnoredAny` does. Formats that have no value for unit variants, such as RON, fail to skip unit and struct variants.
 //! * **`.[]` syntax:** You can use the `.[]` syntax to run the rest of the query for each element in an array and collect the results.
 //!   For example, `.friends.[].name` extracts the `name` field from each element in the `friends` array.
+//!   On a map, the rest of the query runs for each value. For example, `.services.[].image` extracts the `image` field of every service.
+//!   Maps are recognized only in human-readable formats, which describe themselves through `deserialize_any`. Compact formats such as bincode accept only arrays.
 //! * **`.[n]` syntax:** You can use the `.[n]` syntax to extract the nth element from an array.
 //!   For example, `.friends.[0]` extracts the first element of the `friends` array.
+//!   A negative index counts from the end of the array. For example, `.friends.[-1]` extracts the last element.
+//!   If the format tells the length of the array up front (e.g., bincode), the elements are streamed. Otherwise, negative indices buffer the last elements of the array until it ends.
+//! * **Postfix brackets:** As in jq, the dot before a bracket can be omitted after another step.
+//!   For example, `.commits[0].author["login"]` is the same as `.commits.[0].author.["login"]`, and `.friends[].name` is the same as `.friends.[].name`.
+//! * **`.{}` syntax:** You can use the `.{}` syntax to run the rest of the query for each value in a map and collect the results with their keys.
+//!   For example, `.packages.{}.version` extracts the version of each package into a `HashMap<String, String>`.
+//!   The supported containers are `HashMap`, `BTreeMap`, and `Vec<(K, V)>`.
+//! * **`.[start:end]` syntax:** You can use the `.[start:end]` syntax to run the rest of the query for the elements from `start` up to (but not including) `end` and collect the results.
+//!   Either bound can be omitted. For example, `.commits.[:10].sha` extracts the hashes of the first ten commits.
+//!   The elements outside of the slice are skipped without being deserialized.
+//! * **`..field` syntax:** You can use the `..field` syntax to run the rest of the query for every `field` at any depth and collect the results.
+//!   For example, `..version` extracts every `version` field in the document.
+//!   The values of the matching fields are not searched further.
+//!   Note that this query conflicts with any other queries on the same value.
+//! * **`select(...)` syntax:** You can filter the collected values with `| select(...)` right after `.[]`, `.[start:end]`, `.{}`, or `..field`.
+//!   For example, `.items.[] | select(.kind == "Deployment") | .metadata.name` extracts the names of the items whose kind is `Deployment`.
+//!   The predicate is a path optionally compared with a string, a number, a boolean, or `null` using `==`, `!=`, `<`, `<=`, `>`, or `>=`.
+//!   Integers are compared exactly, and numbers are compared as `f64` if either side is a float.
+//!   Without a comparison, the predicate holds if the value at the path is neither null nor false.
+//!   Note that each value is buffered to evaluate the predicate.
+//! * **`//` syntax:** You can list alternative queries separated by `//`. The field takes the result of the first alternative that succeeds.
+//!   For example, `.user.username // .user.login` extracts `username`, or `login` if `username` is missing or invalid.
+//!   The last alternative can be a literal such as `"unknown"`, `3`, `true`, or `null`. For example, `.retries // 3` defaults to 3.
+//!   The document is still traversed once, and the errors are reported only if every alternative fails.
+//! * **Built-in functions:** You can end a query with `| length`, `| keys`, `| has("key")`, or `| type` to compute a property of the value instead of deserializing it.
+//!   For example, `.items | length` counts the items without deserializing them, and `.dependencies | keys` collects the names of the dependencies into a container.
+//!   `length` counts the elements of an array, the entries of a map, or the characters of a string, and gives the absolute value of a number. `keys` collects the keys of a map in sorted order, or the indices of an array.
+//!   `has("key")` evaluates to `bool`, and `type` evaluates to one of `"null"`, `"boolean"`, `"number"`, `"string"`, `"array"`, or `"object"`.
+//!   Note that built-in functions conflict with any other queries on the same value except for other built-in functions.
+//! * **`?` syntax:** You can append `?` to a field or an index to mark it optional.
+//!   If the value is missing or null, the query evaluates to `None` instead of reporting an error.
+//!   For example, `.author?.name` extracts the name of the author into `Option<String>`, and evaluates to `None` when there is no author.
+//!   Appending `?` to `.[]` or a slice instead makes the collection optional: if it is missing or null, the query evaluates to an empty container as in jq.
+//!   For example, `.labels[]?.name` extracts the names of the labels into `Vec<String>`, and evaluates to an empty vector when there are no labels.
+//!   Other errors such as type mismatches are still reported.
+//!
+//! ## JSON Pointer and JSONPath
+//!
+//! A query starting with `/` is a [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901), and a query starting with `$` is a JSONPath.
+//! You can also choose the syntax explicitly with `#[query("...", syntax = "pointer")]`, `syntax = "jsonpath"`, or `syntax = "jq"`.
+//! Both are translated into the jq syntax above, so the same errors are reported.
+//!
+//! * **JSON Pointer:** `/data/items/0/id` is the same as `.data.items.[0].id`. `~1` and `~0` escape `/` and `~` respectively.
+//!   A segment that is an array index (e.g., `0` or `12`, but not `01`) always extracts an element. Use `.["0"]` to extract a field named `0`.
+//! * **JSONPath:** `$.data.items[0].id` is the same as `.data.items.[0].id`.
+//!   The supported selectors are `.name`, `['name']`, `[n]`, `[start:end]`, `.*`, `[*]`, and `..name`, where the wildcards are the same as `.[]`. Filters such as `[?(...)]` and unions are not supported.
+//!
+//! ## Enums
+//!
+//! Deriving on an enum tries the variants in the declaration order, and picks the first variant whose queries all succeed.
+//! This is useful for documents whose shapes differ between versions:
+//!
+//! ```rust
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error + 'static>> {
+//! #[derive(serde_query::Deserialize)]
+//! enum Release {
+//!     V2 {
+//!         #[query(".data.release.tag")]
+//!         tag: String,
+//!     },
+//!     V1(#[query(".tag_name")] String),
+//! }
+//!
+//! let release: Release = serde_json::from_str(r#"{ "tag_name": "v1.0" }"#)?;
+//! assert!(matches!(release, Release::V1(tag) if tag == "v1.0"));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! If every variant fails, the error lists why each variant failed.
+//! Like `#[serde(untagged)]`, the input is buffered before trying the variants, so a self-describing format is required.
+//!
+//! If the variant is determined by a tag in the document, put `#[query(tag = "...")]` on the enum and `#[query(tag_value = "...")]` on each variant.
+//! The tag is a path of fields and indices such as `.type` or `.meta.kind`, and its value must be a string.
+//! The queries of the selected variant run on the whole document.
+//!
+//! ```rust
+//! #[derive(serde_query::Deserialize)]
+//! #[query(tag = ".type")]
+//! enum Event {
+//!     #[query(tag_value = "push")]
+//!     Push(#[query(".payload.ref")] String),
+//!     #[query(tag_value = "star")]
+//!     Star(#[query(".actor.login")] String),
+//! }
+//! ```
+//!
+//! The entries before the top-level field of the tag (`type` in the example) are buffered, and the rest of the document is not.
+//! It is faster when the tag comes first.
+//!
+//! ## Borrowing from the input
+//!
+//! Fields can borrow from the input with a lifetime parameter, e.g. `&'a str`, `&'a [u8]`, or `Cow<'a, str>`.
+//! As with `#[serde(borrow)]`, fields of type `Cow<'a, str>` or `Cow<'a, [u8]>`, optionally inside `Option` or `Vec` (e.g., `Vec<Cow<'a, str>>`), borrow whenever the deserializer allows it.
+//! `Cow` in other types, such as `HashMap<String, Cow<'a, str>>`, deserializes into owned values.
+//! Note that `&'a str` fails on strings that cannot be borrowed, such as JSON strings with escape sequences. Use `Cow<'a, str>` for them.
 //!
 //! [`serde::Deserialize`]: https://docs.serde.rs/serde/trait.Deserialize.html
 //! [`serde_query::Deserialize`]: derive.Deserialize.html
@@ -134,6 +269,15 @@ pub use serde_query_derive::Deserialize;
 /// [module-level documentation]: index.html
 pub use serde_query_derive::DeserializeQuery;
 
+extern crate alloc;
+
+mod borrow;
+mod builtin;
+mod content;
+mod select;
+mod tagged;
+mod variant;
+
 use core::ops::{Deref, DerefMut};
 use serde::de::Deserialize;
 
@@ -164,19 +308,36 @@ where
 // This module can only be used inside the generated code.
 #[doc(hidden)]
 pub mod __priv {
+    pub use crate::borrow::DeserializeBorrowed;
+    pub use crate::builtin::{BuiltinSeed, Builtins};
+    pub use crate::content::{Content, ContentDeserializer, MapKeyDeserializer};
+    pub use crate::select::{select, Comparison, Literal, PathSegment};
+    pub use crate::tagged::{deserialize_tagged, Tagged};
+    pub use crate::variant::{VariantDeserializer, VariantName};
     pub use serde;
 
-    extern crate alloc;
+    use alloc::{borrow::Cow, vec::Vec};
 
     #[derive(Debug)]
     pub struct Error {
-        field: &'static str,
-        prefix: &'static str,
-        message: alloc::borrow::Cow<'static, str>,
+        field: Cow<'static, str>,
+        prefix: Cow<'static, str>,
+        message: Cow<'static, str>,
+        /// The errors of the queries in a nested struct, which are reported instead of this error.
+        nested: Vec<Error>,
     }
 
     impl core::fmt::Display for Error {
         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            if !self.nested.is_empty() {
+                for (index, error) in self.nested.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str("; ")?;
+                    }
+                    error.fmt(f)?;
+                }
+                return Ok(());
+            }
             write!(
                 f,
                 "Query for field '{}' failed at '{}': {}",
@@ -188,50 +349,86 @@ pub mod __priv {
     impl Error {
         pub fn owned(field: &'static str, prefix: &'static str, message: String) -> Self {
             Error {
-                field,
-                prefix,
-                message: alloc::borrow::Cow::Owned(message),
+                field: Cow::Borrowed(field),
+                prefix: Cow::Borrowed(prefix),
+                message: Cow::Owned(message),
+                nested: Vec::new(),
             }
         }
 
         pub fn borrowed(field: &'static str, prefix: &'static str, message: &'static str) -> Self {
             Error {
-                field,
-                prefix,
-                message: alloc::borrow::Cow::Borrowed(message),
+                field: Cow::Borrowed(field),
+                prefix: Cow::Borrowed(prefix),
+                message: Cow::Borrowed(message),
+                nested: Vec::new(),
             }
         }
+
+        /// The errors of a nested struct mounted at `prefix`.
+        ///
+        /// The fields and the paths of the errors are joined with the ones of the parent, e.g.
+        /// `.sha` in the field `commit` at `.commit` becomes `.commit.sha` in the field `commit.sha`.
+        pub fn nested(field: &'static str, prefix: &'static str, errors: Errors) -> Self {
+            let nested = errors
+                .errors
+                .into_iter()
+                .map(|error| Error {
+                    field: Cow::Owned(alloc::format!("{}.{}", field, error.field)),
+                    prefix: join_prefix(prefix, &error.prefix),
+                    message: error.message,
+                    nested: Vec::new(),
+                })
+                .collect();
+            Error {
+                field: Cow::Borrowed(field),
+                prefix: Cow::Borrowed(prefix),
+                message: Cow::Borrowed(""),
+                nested,
+            }
+        }
+    }
+
+    fn join_prefix(prefix: &'static str, nested: &str) -> Cow<'static, str> {
+        if prefix.is_empty() || prefix == "." {
+            Cow::Owned(nested.into())
+        } else if nested == "." {
+            Cow::Borrowed(prefix)
+        } else {
+            Cow::Owned(alloc::format!("{}{}", prefix, nested))
+        }
     }
 
+    /// The errors of the queries of a struct.
     #[derive(Debug)]
-    pub struct Errors<'a> {
-        errors: &'a [Option<Error>],
+    pub struct Errors {
+        /// The errors of nested structs are flattened.
+        errors: Vec<Error>,
     }
 
-    impl<'a> core::fmt::Display for Errors<'a> {
+    impl core::fmt::Display for Errors {
         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-            match self.error_count() {
+            match self.errors.len() {
                 0 => Ok(()),
-                1 => {
-                    let error = self.errors().next().unwrap();
-                    error.fmt(f)
-                }
+                1 => self.errors[0].fmt(f),
                 _ => {
                     write!(f, "Queries failed for fields: ")?;
-                    let mut following = false;
-                    for error in self.errors() {
-                        if following {
+                    let mut previous = None;
+                    for error in self.errors.iter() {
+                        // the alternatives of a field are reported next to each other
+                        if previous == Some(&error.field) {
+                            continue;
+                        }
+                        if previous.is_some() {
                             f.write_str(", ")?;
                         }
                         write!(f, "'{}'", error.field)?;
-                        following = true;
+                        previous = Some(&error.field);
                     }
                     f.write_str("\n")?;
 
-                    let mut index = 1;
-                    for error in self.errors() {
+                    for (index, error) in (1..).zip(self.errors.iter()) {
                         writeln!(f, "  {}. {}", index, error)?;
-                        index += 1;
                     }
 
                     Ok(())
@@ -240,20 +437,409 @@ pub mod __priv {
         }
     }
 
-    impl<'a> Errors<'a> {
-        pub fn new(errors: &'a [Option<Error>]) -> Self {
-            Self { errors }
+    impl Errors {
+        pub fn new<I>(errors: I) -> Self
+        where
+            I: IntoIterator<Item = Option<Error>>,
+        {
+            let mut flattened = Vec::new();
+            for error in errors.into_iter().flatten() {
+                if error.nested.is_empty() {
+                    flattened.push(error);
+                } else {
+                    flattened.extend(error.nested);
+                }
+            }
+            Self { errors: flattened }
+        }
+    }
+
+    /// Deserializes a struct with `#[query("...", nested)]` in another struct.
+    ///
+    /// Unlike `Deserialize`, the errors of the queries are kept apart from the errors of the
+    /// deserializer so that the parent can report them with the full paths.
+    pub trait DeserializeNested<'de>: Sized {
+        fn deserialize_nested<D>(deserializer: D) -> Result<Result<Self, Errors>, D::Error>
+        where
+            D: serde::de::Deserializer<'de>;
+    }
+
+    /// The errors of the variants of an enum, reported when every variant fails.
+    #[derive(Debug)]
+    pub struct VariantErrors<'a, E> {
+        name: &'static str,
+        errors: &'a [(&'static str, E)],
+    }
+
+    impl<'a, E> VariantErrors<'a, E> {
+        pub fn new(name: &'static str, errors: &'a [(&'static str, E)]) -> Self {
+            Self { name, errors }
+        }
+    }
+
+    impl<'a, E: core::fmt::Display> core::fmt::Display for VariantErrors<'a, E> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            writeln!(f, "No variant of '{}' matched", self.name)?;
+            for (variant, error) in self.errors {
+                // indent the errors of the fields under the variant
+                let error = error.to_string();
+                write!(f, "  {}: ", variant)?;
+                for (index, line) in error.trim_end().lines().enumerate() {
+                    if index > 0 {
+                        f.write_str("\n  ")?;
+                    }
+                    f.write_str(line)?;
+                }
+                f.write_str("\n")?;
+            }
+            Ok(())
+        }
+    }
+
+    /// The error message for a query that steps into a variant other than the actual one.
+    pub fn variant_mismatch(expected: &str, found: &VariantName) -> String {
+        alloc::format!(
+            "expected variant '{}', found variant '{}'",
+            expected,
+            found.0
+        )
+    }
+
+    /// Deserializes the literal fallback of a field (e.g., `3` in `.retries // 3`).
+    pub fn literal<'de, T>(
+        field: &'static str,
+        literal: &'static str,
+        content: Content<'de>,
+    ) -> Result<T, Error>
+    where
+        T: serde::de::Deserialize<'de>,
+    {
+        T::deserialize(ContentDeserializer::<serde::de::value::Error>::new(content))
+            .map_err(|e| Error::owned(field, literal, e.to_string()))
+    }
+
+    /// The kind of data the visitor of an optional node expects when the value is present.
+    pub enum Shape {
+        Map,
+        Seq,
+        /// A sequence, or a map in human-readable formats.
+        SeqOrMap,
+        /// A map, or an enum in human-readable formats.
+        MapOrEnum,
+        /// An enum with the variants.
+        Enum(&'static [&'static str]),
+    }
+
+    struct OptionVisitor<V> {
+        shape: Shape,
+        visitor: V,
+    }
+
+    impl<'de, V> serde::de::Visitor<'de> for OptionVisitor<V>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        type Value = Option<V::Value>;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            self.visitor.expecting(formatter)?;
+            formatter.write_str(" or null")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            match self.shape {
+                Shape::Map => deserializer.deserialize_map(self.visitor).map(Some),
+                Shape::Seq => deserializer.deserialize_seq(self.visitor).map(Some),
+                Shape::SeqOrMap => deserialize_seq_or_map(deserializer, self.visitor).map(Some),
+                Shape::MapOrEnum => deserialize_map_or_enum(deserializer, self.visitor).map(Some),
+                Shape::Enum(variants) => deserializer
+                    .deserialize_enum("", variants, self.visitor)
+                    .map(Some),
+            }
+        }
+    }
+
+    /// Deserialize a sequence, or a map in human-readable formats.
+    ///
+    /// Telling a map from a sequence needs `deserialize_any`. Compact formats such as bincode do not
+    /// support it, so only sequences are accepted there.
+    pub fn deserialize_seq_or_map<'de, D, V>(
+        deserializer: D,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+        V: serde::de::Visitor<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(visitor)
+        } else {
+            deserializer.deserialize_seq(visitor)
+        }
+    }
+
+    /// Deserialize a map, or an enum in human-readable formats.
+    ///
+    /// Self-describing formats such as YAML visit the tagged values as enums through `deserialize_any`.
+    /// Compact formats such as bincode do not support it, so only maps are accepted there.
+    pub fn deserialize_map_or_enum<'de, D, V>(
+        deserializer: D,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+        V: serde::de::Visitor<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(visitor)
+        } else {
+            deserializer.deserialize_map(visitor)
+        }
+    }
+
+    /// Deserialize a value that may be null. Returns `None` if the value is null.
+    pub fn deserialize_option<'de, D, V>(
+        deserializer: D,
+        shape: Shape,
+        visitor: V,
+    ) -> Result<Option<V::Value>, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+        V: serde::de::Visitor<'de>,
+    {
+        deserializer.deserialize_option(OptionVisitor { shape, visitor })
+    }
+
+    /// Keeps the last `capacity` elements of a sequence for negative indices.
+    pub struct Window<'de> {
+        elements: alloc::collections::VecDeque<Content<'de>>,
+        capacity: usize,
+        len: usize,
+    }
+
+    impl<'de> Window<'de> {
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                elements: alloc::collections::VecDeque::with_capacity(capacity),
+                capacity,
+                len: 0,
+            }
+        }
+
+        pub fn push(&mut self, element: Content<'de>) {
+            if self.elements.len() == self.capacity {
+                self.elements.pop_front();
+            }
+            self.elements.push_back(element);
+            self.len += 1;
+        }
+
+        /// The number of elements pushed so far.
+        pub fn count(&self) -> usize {
+            self.len
+        }
+
+        /// Takes the `n`th element from the end of the sequence (i.e., `.[-n]`).
+        ///
+        /// Returns `None` if the sequence has less than `n` elements.
+        pub fn take_from_end(&mut self, n: usize) -> Option<Content<'de>> {
+            if n == 0 || n > self.len || n > self.capacity {
+                return None;
+            }
+            let index = self.elements.len() - n;
+            Some(core::mem::replace(&mut self.elements[index], Content::Unit))
+        }
+    }
+
+    /// The values reached by a recursive descent (`..key`).
+    pub trait Descend<'de> {
+        /// The key to look for.
+        const KEY: &'static str;
+
+        /// Run the rest of the query for a value whose key is `KEY`.
+        fn accept<D>(&mut self, deserializer: D) -> Result<(), D::Error>
+        where
+            D: serde::de::Deserializer<'de>;
+    }
+
+    /// Walks every map and sequence in a value, passing the values under `T::KEY` to `T`.
+    ///
+    /// The values passed to `T` are not searched further.
+    pub struct DescendSeed<'a, T>(pub &'a mut T);
+
+    impl<'a, 'de, T> serde::de::DeserializeSeed<'de> for DescendSeed<'a, T>
+    where
+        T: Descend<'de>,
+    {
+        type Value = ();
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    impl<'a, 'de, T> serde::de::Visitor<'de> for DescendSeed<'a, T>
+    where
+        T: Descend<'de>,
+    {
+        type Value = ();
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("any value")
+        }
+
+        fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_i128<E>(self, _v: i128) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_u128<E>(self, _v: u128) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_str<E>(self, _v: &str) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_bytes<E>(self, _v: &[u8]) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(())
+        }
+
+        fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            while seq.next_element_seed(DescendSeed(&mut *self.0))?.is_some() {}
+            Ok(())
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            while let Some(is_key) = map.next_key_seed(KeySeed(T::KEY))? {
+                if is_key {
+                    map.next_value_seed(AcceptSeed(&mut *self.0))?;
+                } else {
+                    map.next_value_seed(DescendSeed(&mut *self.0))?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct AcceptSeed<'a, T>(&'a mut T);
+
+    impl<'a, 'de, T> serde::de::DeserializeSeed<'de> for AcceptSeed<'a, T>
+    where
+        T: Descend<'de>,
+    {
+        type Value = ();
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            self.0.accept(deserializer)
+        }
+    }
+
+    /// Checks whether a map key equals to the given key.
+    struct KeySeed(&'static str);
+
+    impl<'de> serde::de::DeserializeSeed<'de> for KeySeed {
+        type Value = bool;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::de::Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(self)
+        }
+    }
+
+    impl<'de> serde::de::Visitor<'de> for KeySeed {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("a map key")
+        }
+
+        fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+            Ok(false)
         }
 
-        fn error_count(&self) -> usize {
-            self.errors
-                .iter()
-                .map(|opt| if opt.is_some() { 1 } else { 0 })
-                .sum()
+        fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E> {
+            Ok(false)
         }
 
-        fn errors(&self) -> impl Iterator<Item = &Error> {
-            self.errors.iter().flatten()
+        fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E> {
+            Ok(false)
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(v == self.0)
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v == self.0.as_bytes())
         }
     }
 
@@ -283,6 +869,27 @@ pub mod __priv {
         }
     }
 
+    // Used by optional queries collecting elements (e.g., `.items?.[]` into `Option<Vec<T>>`).
+    impl<C: Container> Container for Option<C> {
+        type Element = C::Element;
+
+        fn empty() -> Self {
+            Some(C::empty())
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            if let Some(container) = self {
+                container.reserve(additional);
+            }
+        }
+
+        fn extend_one(&mut self, element: Self::Element) {
+            if let Some(container) = self {
+                container.extend_one(element);
+            }
+        }
+    }
+
     impl<T> Container for alloc::collections::VecDeque<T> {
         type Element = T;
 
@@ -330,4 +937,88 @@ pub mod __priv {
             self.insert(element);
         }
     }
+
+    /// A counterpart of [`Container`] for `.{}` queries that keep the keys.
+    pub trait MapContainer {
+        type Key;
+        type Value;
+
+        fn empty() -> Self;
+
+        fn reserve(&mut self, additional: usize);
+
+        fn insert(&mut self, key: Self::Key, value: Self::Value);
+    }
+
+    impl<M: MapContainer> MapContainer for Option<M> {
+        type Key = M::Key;
+        type Value = M::Value;
+
+        fn empty() -> Self {
+            Some(M::empty())
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            if let Some(container) = self {
+                container.reserve(additional);
+            }
+        }
+
+        fn insert(&mut self, key: Self::Key, value: Self::Value) {
+            if let Some(container) = self {
+                container.insert(key, value);
+            }
+        }
+    }
+
+    impl<K: core::cmp::Eq + core::hash::Hash, V> MapContainer for std::collections::HashMap<K, V> {
+        type Key = K;
+        type Value = V;
+
+        fn empty() -> Self {
+            Self::new()
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.reserve(additional);
+        }
+
+        fn insert(&mut self, key: Self::Key, value: Self::Value) {
+            self.insert(key, value);
+        }
+    }
+
+    impl<K: core::cmp::Ord, V> MapContainer for alloc::collections::BTreeMap<K, V> {
+        type Key = K;
+        type Value = V;
+
+        fn empty() -> Self {
+            Self::new()
+        }
+
+        fn reserve(&mut self, _additional: usize) {
+            // do nothing
+        }
+
+        fn insert(&mut self, key: Self::Key, value: Self::Value) {
+            self.insert(key, value);
+        }
+    }
+
+    impl<K, V> MapContainer for alloc::vec::Vec<(K, V)> {
+        type Key = K;
+        type Value = V;
+
+        fn empty() -> Self {
+            Self::new()
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.reserve(additional);
+        }
+
+        fn insert(&mut self, key: Self::Key, value: Self::Value) {
+            self.push((key, value));
+        }
+    }
 }
@@ -0,0 +1,165 @@
+//! Evaluation of `select(...)` predicates on buffered values.
+
+use alloc::{borrow::Cow, string::ToString};
+use core::{cmp::Ordering, convert::TryFrom};
+
+use crate::content::Content;
+
+pub enum PathSegment {
+    Field(&'static str),
+    Index(usize),
+}
+
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+pub enum Literal {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Number(f64),
+    String(&'static str),
+}
+
+/// A number compared exactly if both sides are integers, and as `f64` otherwise.
+#[derive(Clone, Copy)]
+enum Number {
+    Integer(i128),
+    Float(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(b)),
+            _ => self.as_f64().partial_cmp(&other.as_f64()),
+        }
+    }
+}
+
+/// A value in the order used by jq: null < booleans < numbers < strings < arrays < objects.
+#[derive(PartialEq, PartialOrd)]
+enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Seq,
+    Map,
+}
+
+impl<'a> Value<'a> {
+    fn from_content(content: &'a Content<'_>) -> Self {
+        match content {
+            Content::Bool(v) => Value::Bool(*v),
+            Content::U64(v) => Value::Number(Number::Integer((*v).into())),
+            Content::I64(v) => Value::Number(Number::Integer((*v).into())),
+            Content::U128(v) => Value::Number(match i128::try_from(*v) {
+                Ok(v) => Number::Integer(v),
+                Err(_) => Number::Float(*v as f64),
+            }),
+            Content::I128(v) => Value::Number(Number::Integer(*v)),
+            Content::F64(v) => Value::Number(Number::Float(*v)),
+            Content::Char(v) => Value::String(Cow::Owned(v.to_string())),
+            Content::String(v) => Value::String(Cow::Borrowed(v)),
+            Content::Str(v) => Value::String(Cow::Borrowed(v)),
+            Content::None | Content::Unit => Value::Null,
+            Content::Some(v) | Content::Newtype(v) => Value::from_content(v),
+            Content::ByteBuf(_) | Content::Bytes(_) | Content::Seq(_) => Value::Seq,
+            Content::Map(_) => Value::Map,
+        }
+    }
+
+    fn from_literal(literal: &Literal) -> Self {
+        match literal {
+            Literal::Null => Value::Null,
+            Literal::Bool(v) => Value::Bool(*v),
+            Literal::Integer(v) => Value::Number(Number::Integer(*v)),
+            Literal::Number(v) => Value::Number(Number::Float(*v)),
+            Literal::String(v) => Value::String(Cow::Borrowed(v)),
+        }
+    }
+}
+
+fn unwrap<'a, 'de>(content: &'a Content<'de>) -> &'a Content<'de> {
+    match content {
+        Content::Some(v) | Content::Newtype(v) => unwrap(v),
+        _ => content,
+    }
+}
+
+pub(crate) fn key_matches(key: &Content<'_>, name: &str) -> bool {
+    match unwrap(key) {
+        Content::String(key) => key == name,
+        Content::Str(key) => *key == name,
+        _ => false,
+    }
+}
+
+/// Looks up the value at `path`. Returns `None` if the path does not exist.
+pub(crate) fn lookup<'a, 'de>(
+    content: &'a Content<'de>,
+    path: &[PathSegment],
+) -> Option<&'a Content<'de>> {
+    let mut content = unwrap(content);
+    for segment in path {
+        content = match (segment, content) {
+            (PathSegment::Field(name), Content::Map(entries)) => {
+                &entries.iter().find(|(key, _)| key_matches(key, name))?.1
+            }
+            (PathSegment::Index(index), Content::Seq(elements)) => elements.get(*index)?,
+            _ => return None,
+        };
+        content = unwrap(content);
+    }
+    Some(content)
+}
+
+/// Evaluates `select(<path> [<comparison> <literal>])` on `content`.
+///
+/// A missing path evaluates to null. Without a comparison, the predicate holds if the value is neither null nor false.
+pub fn select(
+    content: &Content<'_>,
+    path: &[PathSegment],
+    comparison: Option<(Comparison, Literal)>,
+) -> bool {
+    let value = match lookup(content, path) {
+        Some(content) => Value::from_content(content),
+        None => Value::Null,
+    };
+    match comparison {
+        None => !matches!(value, Value::Null | Value::Bool(false)),
+        Some((comparison, literal)) => {
+            let ordering = value.partial_cmp(&Value::from_literal(&literal));
+            match comparison {
+                Comparison::Eq => ordering == Some(Ordering::Equal),
+                Comparison::Ne => ordering != Some(Ordering::Equal),
+                Comparison::Lt => ordering == Some(Ordering::Less),
+                Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                Comparison::Gt => ordering == Some(Ordering::Greater),
+                Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            }
+        }
+    }
+}
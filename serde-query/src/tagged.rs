@@ -0,0 +1,182 @@
+//! Enums selected by a tag at a path (e.g., `#[query(tag = ".type")]`).
+//!
+//! The entries of the document are buffered until the top-level field of the tag arrives. Then the
+//! selected variant reads the buffered entries followed by the rest of the document, which is not
+//! buffered.
+
+use alloc::{format, string::String, vec};
+use core::{fmt, marker::PhantomData};
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+
+use crate::{
+    content::{Content, ContentDeserializer},
+    select::{key_matches, lookup, PathSegment},
+};
+
+/// An enum whose variant is selected by a tag.
+pub trait Tagged<'de>: Sized {
+    /// The name of the enum.
+    const NAME: &'static str;
+    /// The path to the tag. It starts with a field.
+    const TAG: &'static [PathSegment];
+
+    fn deserialize_variant<D>(tag: &str, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+pub fn deserialize_tagged<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Tagged<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(TaggedVisitor(PhantomData))
+}
+
+fn tag_string(tag: &[PathSegment]) -> String {
+    let mut ret = String::new();
+    for segment in tag {
+        match segment {
+            PathSegment::Field(name) => {
+                ret.push('.');
+                ret.push_str(name);
+            }
+            PathSegment::Index(index) => ret.push_str(&format!(".[{}]", index)),
+        }
+    }
+    ret
+}
+
+struct TaggedVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+where
+    T: Tagged<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a map with the tag '{}'", tag_string(T::TAG))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let (field, rest) = match T::TAG.split_first() {
+            Some((PathSegment::Field(field), rest)) => (field, rest),
+            _ => unreachable!("the tag starts with a field"),
+        };
+
+        let mut buffered = vec![];
+        while let Some(key) = map.next_key::<Content<'de>>()? {
+            let value = map.next_value::<Content<'de>>()?;
+            let is_tag = key_matches(&key, field);
+            buffered.push((key, value));
+            if !is_tag {
+                continue;
+            }
+
+            let tag = match lookup(&buffered[buffered.len() - 1].1, rest) {
+                Some(Content::String(tag)) => tag.clone(),
+                Some(Content::Str(tag)) => String::from(*tag),
+                _ => {
+                    return Err(de::Error::custom(format!(
+                        "the tag '{}' of '{}' is not a string",
+                        tag_string(T::TAG),
+                        T::NAME
+                    )))
+                }
+            };
+            let mut chain = Chain {
+                buffered: buffered.into_iter(),
+                value: None,
+                map,
+                done: false,
+            };
+            let value = T::deserialize_variant(&tag, &mut chain)?;
+            // skip the entries that the variant did not read
+            if !chain.done {
+                while chain.next_key::<IgnoredAny>()?.is_some() {
+                    chain.next_value::<IgnoredAny>()?;
+                }
+            }
+            return Ok(value);
+        }
+
+        Err(de::Error::custom(format!(
+            "missing the tag '{}' of '{}'",
+            tag_string(T::TAG),
+            T::NAME
+        )))
+    }
+}
+
+/// A map of the buffered entries followed by the rest of `map`.
+struct Chain<'de, A> {
+    buffered: vec::IntoIter<(Content<'de>, Content<'de>)>,
+    value: Option<Content<'de>>,
+    map: A,
+    /// Whether `map` has no more entries.
+    done: bool,
+}
+
+impl<'de, A> Deserializer<'de> for &mut Chain<'de, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, A> MapAccess<'de> for Chain<'de, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.buffered.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ContentDeserializer::new(key)).map(Some)
+            }
+            None if self.done => Ok(None),
+            None => {
+                let key = self.map.next_key_seed(seed)?;
+                self.done = key.is_none();
+                Ok(key)
+            }
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ContentDeserializer::new(value)),
+            None => self.map.next_value_seed(seed),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint().map(|len| len + self.buffered.len())
+    }
+}
@@ -0,0 +1,303 @@
+//! Stepping into enum variants with `!Variant` and `.Variant`.
+//!
+//! The generated code reads the variant name with [`VariantName`] and deserializes the payload of
+//! a matching variant from [`VariantDeserializer`].
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use serde::de::{self, DeserializeSeed, Deserializer, VariantAccess, Visitor};
+
+/// The name of a variant, read as an identifier.
+pub struct VariantName(pub String);
+
+impl<'de> de::Deserialize<'de> for VariantName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(VariantNameVisitor)
+    }
+}
+
+struct VariantNameVisitor;
+
+impl<'de> Visitor<'de> for VariantNameVisitor {
+    type Value = VariantName;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a variant name")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(VariantName(v.into()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(VariantName(String::from_utf8_lossy(v).into_owned()))
+    }
+
+    // formats without names give the index of the variant
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(VariantName(v.to_string()))
+    }
+}
+
+/// A deserializer for the payload of a variant.
+///
+/// The kind of the variant follows from how the payload is deserialized: a map or a struct is a
+/// struct variant, a sequence or a tuple is a tuple variant, a unit is a unit variant, and anything
+/// else is a newtype variant.
+pub struct VariantDeserializer<A> {
+    access: A,
+    /// Whether `deserialize_any` reads a struct variant instead of a newtype variant.
+    fields: bool,
+}
+
+impl<A> VariantDeserializer<A> {
+    pub fn new(access: A) -> Self {
+        VariantDeserializer {
+            access,
+            fields: false,
+        }
+    }
+
+    /// A deserializer for a payload whose fields are queried (e.g., `.Push.ref`).
+    ///
+    /// The fields look into the payload with `deserialize_any` in human-readable formats, which
+    /// would otherwise read a newtype variant.
+    pub fn fields(access: A) -> Self {
+        VariantDeserializer {
+            access,
+            fields: true,
+        }
+    }
+}
+
+/// A `Deserializer` method called on the payload of a newtype variant.
+enum Method {
+    Any,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    String,
+    Bytes,
+    ByteBuf,
+    Option,
+    NewtypeStruct(&'static str),
+    Enum(&'static str, &'static [&'static str]),
+    Identifier,
+    IgnoredAny,
+}
+
+struct Forward<V> {
+    method: Method,
+    visitor: V,
+}
+
+impl<'de, V> DeserializeSeed<'de> for Forward<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = self.visitor;
+        match self.method {
+            Method::Any => deserializer.deserialize_any(visitor),
+            Method::Bool => deserializer.deserialize_bool(visitor),
+            Method::I8 => deserializer.deserialize_i8(visitor),
+            Method::I16 => deserializer.deserialize_i16(visitor),
+            Method::I32 => deserializer.deserialize_i32(visitor),
+            Method::I64 => deserializer.deserialize_i64(visitor),
+            Method::I128 => deserializer.deserialize_i128(visitor),
+            Method::U8 => deserializer.deserialize_u8(visitor),
+            Method::U16 => deserializer.deserialize_u16(visitor),
+            Method::U32 => deserializer.deserialize_u32(visitor),
+            Method::U64 => deserializer.deserialize_u64(visitor),
+            Method::U128 => deserializer.deserialize_u128(visitor),
+            Method::F32 => deserializer.deserialize_f32(visitor),
+            Method::F64 => deserializer.deserialize_f64(visitor),
+            Method::Char => deserializer.deserialize_char(visitor),
+            Method::Str => deserializer.deserialize_str(visitor),
+            Method::String => deserializer.deserialize_string(visitor),
+            Method::Bytes => deserializer.deserialize_bytes(visitor),
+            Method::ByteBuf => deserializer.deserialize_byte_buf(visitor),
+            Method::Option => deserializer.deserialize_option(visitor),
+            Method::NewtypeStruct(name) => deserializer.deserialize_newtype_struct(name, visitor),
+            Method::Enum(name, variants) => deserializer.deserialize_enum(name, variants, visitor),
+            Method::Identifier => deserializer.deserialize_identifier(visitor),
+            Method::IgnoredAny => deserializer.deserialize_ignored_any(visitor),
+        }
+    }
+}
+
+macro_rules! forward_to_newtype_variant {
+    ($($method:ident => $forward:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.access.newtype_variant_seed(Forward {
+                    method: Method::$forward,
+                    visitor,
+                })
+            }
+        )*
+    };
+}
+
+impl<'de, A> Deserializer<'de> for VariantDeserializer<A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    forward_to_newtype_variant! {
+        deserialize_bool => Bool,
+        deserialize_i8 => I8,
+        deserialize_i16 => I16,
+        deserialize_i32 => I32,
+        deserialize_i64 => I64,
+        deserialize_i128 => I128,
+        deserialize_u8 => U8,
+        deserialize_u16 => U16,
+        deserialize_u32 => U32,
+        deserialize_u64 => U64,
+        deserialize_u128 => U128,
+        deserialize_f32 => F32,
+        deserialize_f64 => F64,
+        deserialize_char => Char,
+        deserialize_str => Str,
+        deserialize_string => String,
+        deserialize_bytes => Bytes,
+        deserialize_byte_buf => ByteBuf,
+        deserialize_option => Option,
+        deserialize_identifier => Identifier,
+        deserialize_ignored_any => IgnoredAny,
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.fields {
+            self.access.struct_variant(&[], visitor)
+        } else {
+            self.access.newtype_variant_seed(Forward {
+                method: Method::Any,
+                visitor,
+            })
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.newtype_variant_seed(Forward {
+            method: Method::NewtypeStruct(name),
+            visitor,
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.newtype_variant_seed(Forward {
+            method: Method::Enum(name, variants),
+            visitor,
+        })
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.unit_variant()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    // The length is unknown, which self-describing formats ignore.
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.tuple_variant(0, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.tuple_variant(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.tuple_variant(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.struct_variant(&[], visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.access.struct_variant(fields, visitor)
+    }
+}
@@ -0,0 +1,91 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct User {
+    #[query(".user.username // .user.login")]
+    name: String,
+    #[query(".retries // .settings.retries // 3")]
+    retries: u32,
+    #[query(r#".display_name // "anonymous""#)]
+    display_name: String,
+    #[query(".email // null")]
+    email: Option<String>,
+}
+
+#[test]
+fn test_alternative_first() {
+    let document = serde_json::json!({
+        "user": { "username": "kou", "login": "kou-old" },
+        "retries": 5,
+        "display_name": "Kou",
+        "email": "kou@example.com",
+    })
+    .to_string();
+
+    let user: User = serde_json::from_str(&document).unwrap();
+    assert_eq!(user.name, "kou");
+    assert_eq!(user.retries, 5);
+    assert_eq!(user.display_name, "Kou");
+    assert_eq!(user.email.as_deref(), Some("kou@example.com"));
+}
+
+#[test]
+fn test_alternative_fallback() {
+    let document = serde_json::json!({
+        "user": { "login": "kou" },
+        "settings": { "retries": 2 },
+    })
+    .to_string();
+
+    let user: User = serde_json::from_str(&document).unwrap();
+    assert_eq!(user.name, "kou");
+    assert_eq!(user.retries, 2);
+    assert_eq!(user.display_name, "anonymous");
+    assert_eq!(user.email, None);
+
+    let document = serde_json::json!({
+        "user": { "username": null, "login": "kou" },
+        "retries": "many",
+    })
+    .to_string();
+
+    let user: User = serde_json::from_str(&document).unwrap();
+    assert_eq!(user.name, "kou");
+    assert_eq!(user.retries, 3);
+}
+
+#[test]
+fn test_alternative_errors() {
+    let document = serde_json::json!({
+        "user": {},
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<User>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: 'name'
+  1. Query for field 'name' failed at '.user': missing field 'username'
+  2. Query for field 'name' failed at '.user': missing field 'login'
+
+"#
+    );
+
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(r#".count // "many""#)]
+        _count: u32,
+    }
+
+    let error = serde_json::from_str::<Data>("{}").unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: '_count'
+  1. Query for field '_count' failed at '.': missing field 'count'
+  2. Query for field '_count' failed at '"many"': invalid type: string "many", expected u32
+
+"#
+    );
+}
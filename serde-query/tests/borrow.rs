@@ -0,0 +1,95 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use serde_query::{Deserialize, DeserializeQuery, Query};
+
+#[derive(Deserialize)]
+struct Row<'a> {
+    #[query(".name")]
+    name: &'a str,
+    #[query(".tags[]")]
+    tags: Vec<&'a str>,
+    #[query(".raw")]
+    raw: &'a [u8],
+    #[query(".description")]
+    description: Cow<'a, str>,
+    #[query(".authors[].login")]
+    authors: Vec<Cow<'a, str>>,
+    #[query(".license?.name")]
+    license: Option<Cow<'a, str>>,
+    #[query(".names")]
+    names: Vec<Cow<'a, str>>,
+}
+
+#[test]
+fn test_borrow() {
+    let document = r#"{
+        "name": "serde-query",
+        "tags": ["serde", "query"],
+        "raw": "bytes",
+        "description": "a query\nlanguage",
+        "authors": [{ "login": "pandaman64" }],
+        "license": { "name": "MIT" },
+        "names": ["plain", "esc\taped"]
+    }"#;
+
+    let row: Row = serde_json::from_str(document).unwrap();
+    assert_eq!(row.name, "serde-query");
+    assert_eq!(row.tags, ["serde", "query"]);
+    assert_eq!(row.raw, b"bytes");
+    // escape sequences cannot be borrowed
+    assert_eq!(row.description, "a query\nlanguage");
+    assert!(matches!(row.description, Cow::Owned(_)));
+    assert_eq!(row.authors, ["pandaman64"]);
+    assert!(matches!(row.authors[0], Cow::Borrowed(_)));
+    assert!(matches!(row.license, Some(Cow::Borrowed("MIT"))));
+    assert!(matches!(row.names[0], Cow::Borrowed("plain")));
+    assert!(matches!(row.names[1], Cow::Owned(_)));
+}
+
+#[derive(DeserializeQuery)]
+struct Last<'a> {
+    #[query(".items[-1].name")]
+    name: &'a str,
+}
+
+#[test]
+fn test_borrow_buffered() {
+    let document = r#"{ "items": [{ "name": "first" }, { "name": "last" }] }"#;
+
+    let last: Last = serde_json::from_str::<Query<Last>>(document)
+        .unwrap()
+        .into();
+    assert_eq!(last.name, "last");
+}
+
+#[derive(Deserialize)]
+struct Owned<'a> {
+    #[query(".labels")]
+    labels: HashMap<String, Cow<'a, str>>,
+    #[query(".pair")]
+    pair: (Cow<'a, str>, u32),
+    #[query(".boxed")]
+    boxed: Box<Cow<'a, str>>,
+    #[query(".bytes")]
+    bytes: Option<Vec<Cow<'a, [u8]>>>,
+}
+
+#[test]
+fn test_borrow_only_plain_cow() {
+    let document = r#"{
+        "labels": { "kind": "tool" },
+        "pair": ["one", 1],
+        "boxed": "boxed",
+        "bytes": ["raw"]
+    }"#;
+
+    let owned: Owned = serde_json::from_str(document).unwrap();
+    // types other than `Cow` in `Option` or `Vec` use `Deserialize`, which does not borrow
+    assert!(matches!(owned.labels["kind"], Cow::Owned(_)));
+    assert!(matches!(owned.pair, (Cow::Owned(_), 1)));
+    assert!(matches!(*owned.boxed, Cow::Owned(_)));
+    assert!(matches!(
+        owned.bytes.as_deref(),
+        Some([Cow::Borrowed(b"raw")])
+    ));
+}
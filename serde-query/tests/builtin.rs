@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+
+use serde_query::Deserialize;
+
+#[test]
+fn test_builtin() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".items | length")]
+        item_count: usize,
+        #[query(".dependencies | length")]
+        dependency_count: u32,
+        #[query(".dependencies | keys")]
+        dependency_names: Vec<String>,
+        #[query(".items | keys")]
+        item_indices: Vec<usize>,
+        #[query(r#".dependencies | has("serde")"#)]
+        has_serde: bool,
+        #[query(r#".dependencies | has("tokio")"#)]
+        has_tokio: bool,
+        #[query(".name | length")]
+        name_length: usize,
+        #[query(".offset | length")]
+        offset_length: u64,
+        #[query(".ratio | length")]
+        ratio_length: f64,
+    }
+
+    // keys are sorted as in jq
+    let document = r#"
+{
+    "name": "päckage",
+    "items": [1, "two", { "three": 3 }, [4], null, true],
+    "dependencies": { "serde": "1", "logos": "0.12" },
+    "offset": -3,
+    "ratio": -0.5
+}
+"#;
+
+    let data: Data = serde_json::from_str(document).unwrap();
+    assert_eq!(data.item_count, 6);
+    assert_eq!(data.dependency_count, 2);
+    assert_eq!(data.dependency_names, vec!["logos", "serde"]);
+    assert_eq!(data.item_indices, vec![0, 1, 2, 3, 4, 5]);
+    assert!(data.has_serde);
+    assert!(!data.has_tokio);
+    assert_eq!(data.name_length, 7);
+    assert_eq!(data.offset_length, 3);
+    assert_eq!(data.ratio_length, 0.5);
+}
+
+#[test]
+fn test_builtin_in_collection() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".items.[] | type")]
+        item_types: BTreeSet<String>,
+        #[query(r#".words.[] | select(. != null) | length"#)]
+        lengths: Vec<usize>,
+    }
+
+    let document = serde_json::json!({
+        "items": ["two", { "three": 3 }, [4, 4], null],
+        "words": ["one", null, "three"],
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str(&document).unwrap();
+    assert_eq!(
+        data.item_types,
+        BTreeSet::from(["string", "object", "array", "null"].map(String::from))
+    );
+    assert_eq!(data.lengths, vec![3, 5]);
+}
+
+#[test]
+fn test_builtin_errors() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".enabled | length")]
+        _enabled_length: usize,
+        #[query(r#".items | has("a")"#)]
+        _has: bool,
+        #[query(".items | length")]
+        _item_count: u8,
+    }
+
+    let document = serde_json::json!({
+        "enabled": true,
+        "items": vec![0; 300],
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: '_enabled_length', '_has', '_item_count'
+  1. Query for field '_enabled_length' failed at '.enabled': cannot compute 'length' of boolean
+  2. Query for field '_has' failed at '.items': cannot compute 'has' of array
+  3. Query for field '_item_count' failed at '.items': invalid value: integer `300`, expected u8
+
+"#
+    );
+}
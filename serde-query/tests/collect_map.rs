@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+
+use serde_query::Deserialize;
+
+#[test]
+fn test_collect_map_values() {
+    #[derive(Debug, Deserialize)]
+    struct Compose {
+        #[query(".services.[].image")]
+        images: BTreeSet<String>,
+    }
+
+    let document = serde_json::json!({
+        "services": {
+            "web": { "image": "nginx", "ports": ["80:80"] },
+            "db": { "image": "postgres" },
+        },
+    })
+    .to_string();
+
+    let compose: Compose = serde_json::from_str(&document).unwrap();
+    assert_eq!(
+        compose.images,
+        BTreeSet::from(["nginx".to_string(), "postgres".to_string()])
+    );
+}
+
+#[test]
+fn test_collect_array_of_map() {
+    #[derive(Debug, Deserialize)]
+    struct Compose {
+        #[query(".services.[].image")]
+        images: Vec<String>,
+    }
+
+    let document = serde_json::json!({
+        "services": { "web": { "image": "nginx" } },
+    })
+    .to_string();
+
+    let compose: Compose = serde_json::from_str(&document).unwrap();
+    assert_eq!(compose.images, ["nginx"]);
+
+    let document = "services:\n  web:\n    image: nginx\n  db:\n    image: postgres\n";
+
+    let compose: Compose = serde_yaml::from_str(document).unwrap();
+    assert_eq!(compose.images, ["nginx", "postgres"]);
+}
+
+#[test]
+fn test_collect_array_non_self_describing() {
+    // compact formats cannot tell a map from a sequence, so `.[]` reads a sequence there
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".[]")]
+        values: Vec<u32>,
+    }
+
+    let document = bincode::serialize(&vec![1u32, 2, 3]).unwrap();
+
+    let data: Data = bincode::deserialize(&document).unwrap();
+    assert_eq!(data.values, [1, 2, 3]);
+}
+
+#[test]
+fn test_collect_map_values_error() {
+    #[derive(Debug, Deserialize)]
+    struct Compose {
+        #[query(".services.[].image")]
+        _images: Vec<String>,
+    }
+
+    let document = serde_json::json!({
+        "services": {
+            "web": { "image": "nginx" },
+            "db": { "build": "." },
+        },
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Compose>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '_images' failed at '.services.[]': missing field 'image'"#
+    );
+}
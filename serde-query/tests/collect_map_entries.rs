@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_query::Deserialize;
+
+#[test]
+fn test_collect_map_entries() {
+    #[derive(Debug, Deserialize)]
+    struct Lockfile {
+        #[query(".packages.{}.version")]
+        versions: HashMap<String, String>,
+        #[query(".packages.{}.license")]
+        licenses: Vec<(String, String)>,
+        #[query(".dependencies")]
+        dependencies: BTreeMap<String, String>,
+    }
+
+    let document = r#"
+{
+    "packages": {
+        "serde": { "version": "1.0.152", "license": "MIT OR Apache-2.0" },
+        "logos": { "version": "0.12.1", "license": "MIT OR Apache-2.0" }
+    },
+    "dependencies": {
+        "serde": "1"
+    }
+}
+"#;
+
+    let lockfile: Lockfile = serde_json::from_str(document).unwrap();
+    assert_eq!(
+        lockfile.versions,
+        HashMap::from([
+            ("serde".to_string(), "1.0.152".to_string()),
+            ("logos".to_string(), "0.12.1".to_string()),
+        ])
+    );
+    assert_eq!(
+        lockfile.licenses,
+        vec![
+            ("serde".to_string(), "MIT OR Apache-2.0".to_string()),
+            ("logos".to_string(), "MIT OR Apache-2.0".to_string()),
+        ]
+    );
+    assert_eq!(
+        lockfile.dependencies,
+        BTreeMap::from([("serde".to_string(), "1".to_string())])
+    );
+}
+
+#[test]
+fn test_collect_map_entries_integer_keys() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".m.{}")]
+        m: HashMap<u32, String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Shared {
+        #[query(".m.{}")]
+        m: BTreeMap<u32, String>,
+        #[query(".m.{}")]
+        raw: BTreeMap<String, String>,
+    }
+
+    let document = r#"{ "m": { "1": "a", "20": "b" } }"#;
+
+    let data: Data = serde_json::from_str(document).unwrap();
+    assert_eq!(
+        data.m,
+        HashMap::from([(1, "a".to_string()), (20, "b".to_string())])
+    );
+
+    let shared: Shared = serde_json::from_str(document).unwrap();
+    assert_eq!(
+        shared.m,
+        BTreeMap::from([(1, "a".to_string()), (20, "b".to_string())])
+    );
+    assert_eq!(
+        shared.raw,
+        BTreeMap::from([
+            ("1".to_string(), "a".to_string()),
+            ("20".to_string(), "b".to_string())
+        ])
+    );
+}
+
+#[test]
+fn test_collect_map_entries_key_error() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".scores.{}")]
+        _scores: BTreeMap<u32, u32>,
+    }
+
+    let document = serde_json::json!({
+        "scores": { "Kou": 100 },
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        "invalid value: expected key to be a number in quotes at line 1 column 12"
+    );
+
+    // a buffered key only fails the query that expects another type
+    #[derive(Debug, Deserialize)]
+    struct Shared {
+        #[query(".scores.{}")]
+        _scores: BTreeMap<u32, u32>,
+        #[query(".scores.{}")]
+        _names: BTreeMap<String, u32>,
+    }
+
+    let error = serde_json::from_str::<Shared>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '_scores' failed at '.scores': invalid type: string "Kou", expected u32"#
+    );
+}
@@ -14,10 +14,19 @@ struct B {
     expect_struct: String,
 }
 
+#[derive(serde_query::Deserialize)]
+struct C {
+    #[query(r#".foo.[0:2]"#)]
+    head: Vec<String>,
+    #[query(r#".foo.[2:]"#)]
+    tail: Vec<String>,
+}
+
 fn assert_deserialize<'de, D: serde::Deserialize<'de>>() {}
 
 fn main() {
     // ensure that fallback implemenations work
     assert_deserialize::<A>();
     assert_deserialize::<B>();
+    assert_deserialize::<C>();
 }
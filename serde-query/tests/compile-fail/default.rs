@@ -0,0 +1,21 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+struct LiteralFallback {
+    #[query(".timeout // 30", default)]
+    timeout: u64,
+}
+
+#[derive(Deserialize)]
+struct InvalidPath {
+    #[query(".timeout", default = "30")]
+    timeout: u64,
+}
+
+#[derive(Deserialize)]
+struct Duplicated {
+    #[query(".timeout", default, default)]
+    timeout: u64,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use serde_query::Deserialize;
+
+mod hex {
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <u32 as serde::Deserialize>::deserialize(deserializer)
+    }
+}
+
+#[derive(Deserialize)]
+struct LiteralFallback {
+    #[query(".color // 0", with = "hex")]
+    color: u32,
+}
+
+#[derive(Deserialize)]
+struct BuiltinFunction {
+    #[query(".colors | length", with = "hex")]
+    count: u32,
+}
+
+#[derive(Deserialize)]
+struct Duplicated {
+    #[query(".color", with = "hex", deserialize_with = "hex::deserialize")]
+    color: u32,
+}
+
+fn main() {}
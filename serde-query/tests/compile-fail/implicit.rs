@@ -0,0 +1,26 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+#[query(implicit, rename_all = "camel_case")]
+struct UnknownCase {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[query(rename_all = "camelCase")]
+struct WithoutImplicit {
+    #[query(".name")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[query(implicit)]
+struct Tuple(String);
+
+#[derive(Deserialize)]
+#[query(implicit = "yes", tag = ".type")]
+struct InvalidArguments {
+    name: String,
+}
+
+fn main() {}
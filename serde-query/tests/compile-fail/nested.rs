@@ -0,0 +1,21 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+struct Author {
+    #[query(".name")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct BuiltinFunction {
+    #[query(".authors | length", nested)]
+    count: Author,
+}
+
+#[derive(Deserialize)]
+struct Adapter {
+    #[query(".author", nested, with = "author")]
+    author: Author,
+}
+
+fn main() {}
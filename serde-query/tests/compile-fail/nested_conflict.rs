@@ -0,0 +1,25 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+struct CommitInfo {
+    #[query(".message")]
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    #[query(".commit", nested)]
+    commit: CommitInfo,
+    #[query(".commit.sha")]
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct Reversed {
+    #[query(".commit.author.name")]
+    author: String,
+    #[query(".commit", nested)]
+    commit: CommitInfo,
+}
+
+fn main() {}
@@ -6,6 +6,16 @@ struct A {
     field_in_bracket: String,
     #[query(r#".😎"#)]
     unsupported_char: String,
+    #[query(r#".kind | select(. == "Deployment")"#)]
+    select_without_collector: String,
+    #[query("data/items", syntax = "pointer")]
+    pointer_without_slash: String,
+    #[query("$.items[?(@.id)]")]
+    jsonpath_filter: String,
+    #[query(".items", syntax = "xpath")]
+    unknown_syntax: String,
+    #[query(r#".["\uD800"]"#)]
+    unpaired_surrogate: String,
 }
 
 fn assert_deserialize<'de, D: serde::Deserialize<'de>>() {}
@@ -0,0 +1,17 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+#[query(root = ".items[]")]
+struct CollectingRoot {
+    #[query(".name")]
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[query(root = "data")]
+struct InvalidRoot {
+    #[query(".name")]
+    name: String,
+}
+
+fn main() {}
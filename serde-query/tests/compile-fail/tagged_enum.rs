@@ -0,0 +1,26 @@
+use serde_query::Deserialize;
+
+#[derive(Deserialize)]
+#[query(tag = ".type")]
+enum MissingTagValue {
+    #[query(tag_value = "a")]
+    A(#[query(".a")] u32),
+    B(#[query(".b")] u32),
+    #[query(tag_value = "a")]
+    C(#[query(".c")] u32),
+}
+
+#[derive(Deserialize)]
+enum MissingTag {
+    #[query(tag_value = "a")]
+    A(#[query(".a")] u32),
+}
+
+#[derive(Deserialize)]
+#[query(tag = ".kinds[]")]
+enum InvalidTag {
+    #[query(tag_value = "a")]
+    A(#[query(".a")] u32),
+}
+
+fn main() {}
@@ -0,0 +1,82 @@
+use serde_query::Deserialize;
+
+fn default_timeout() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    #[query(".server.timeout", default = "default_timeout")]
+    timeout: u64,
+    #[query(".server.retries", default)]
+    retries: u32,
+    #[query(".server.hosts[]", default)]
+    hosts: Vec<String>,
+    #[query(".server.ports.[1]", default)]
+    backup_port: u16,
+    #[query(".server.name // .name", default = "String::new")]
+    name: String,
+}
+
+#[test]
+fn test_default() {
+    let document = serde_json::json!({
+        "server": { "timeout": 10, "hosts": ["a", "b"], "ports": [80, 8080] },
+        "name": "web",
+    })
+    .to_string();
+    let config: Config = serde_json::from_str(&document).unwrap();
+    assert_eq!(config.timeout, 10);
+    assert_eq!(config.retries, 0);
+    assert_eq!(config.hosts, ["a", "b"]);
+    assert_eq!(config.backup_port, 8080);
+    assert_eq!(config.name, "web");
+
+    // missing at every depth
+    let config: Config = serde_json::from_str("{}").unwrap();
+    assert_eq!(config.timeout, 30);
+    assert_eq!(config.retries, 0);
+    assert!(config.hosts.is_empty());
+    assert_eq!(config.backup_port, 0);
+    assert_eq!(config.name, "");
+
+    let document = serde_json::json!({ "server": { "ports": [80] } }).to_string();
+    let config: Config = serde_json::from_str(&document).unwrap();
+    assert_eq!(config.backup_port, 0);
+}
+
+#[test]
+fn test_default_errors() {
+    // type errors are still reported
+    let document = serde_json::json!({
+        "server": { "timeout": "10", "retries": null },
+    })
+    .to_string();
+    let error = serde_json::from_str::<Config>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: 'retries', 'timeout'
+  1. Query for field 'retries' failed at '.server.retries': invalid type: null, expected u32 at line 1 column 25
+  2. Query for field 'timeout' failed at '.server.timeout': invalid type: string "10", expected u64 at line 1 column 40
+
+"#
+    );
+
+    // the elements do not take the default
+    #[derive(Debug, Deserialize)]
+    struct Ports {
+        #[query(".servers[].port", default)]
+        ports: Vec<u16>,
+    }
+
+    let document = serde_json::json!({ "servers": [{ "port": 80 }, {}] }).to_string();
+    let error = serde_json::from_str::<Ports>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'ports' failed at '.servers.[]': missing field 'port'"#
+    );
+
+    let ports: Ports = serde_json::from_str("{}").unwrap();
+    assert!(ports.ports.is_empty());
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize as _, Deserializer};
+use serde_query::Deserialize;
+
+/// Parses a duration like `"90s"` into seconds.
+fn seconds<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.strip_suffix('s')
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {:?}", s)))
+}
+
+mod hex {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <&str>::deserialize(deserializer)?;
+        u32::from_str_radix(s.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Job {
+    #[query(".spec.timeout", deserialize_with = "seconds")]
+    timeout: u64,
+    #[query(".spec.color", with = "hex")]
+    color: u32,
+    #[query(".spec.steps[].timeout", deserialize_with = "seconds")]
+    step_timeouts: Vec<u64>,
+    #[query(".spec.palette.{}", with = "hex")]
+    palette: std::collections::BTreeMap<String, u32>,
+}
+
+#[test]
+fn test_deserialize_with() {
+    let document = serde_json::json!({
+        "spec": {
+            "timeout": "90s",
+            "color": "0xff8800",
+            "steps": [{ "timeout": "10s" }, { "timeout": "20s" }],
+            "palette": { "red": "ff0000", "blue": "0000ff" },
+        },
+    })
+    .to_string();
+
+    let job: Job = serde_json::from_str(&document).unwrap();
+    assert_eq!(job.timeout, 90);
+    assert_eq!(job.color, 0xff8800);
+    assert_eq!(job.step_timeouts, [10, 20]);
+    assert_eq!(job.palette["red"], 0xff0000);
+    assert_eq!(job.palette["blue"], 0x0000ff);
+}
+
+#[test]
+fn test_deserialize_with_default() {
+    #[derive(Debug, Deserialize)]
+    struct Timeout {
+        #[query(".timeout", deserialize_with = "seconds", default)]
+        timeout: u64,
+    }
+
+    let timeout: Timeout = serde_json::from_str(r#"{"timeout": "5s"}"#).unwrap();
+    assert_eq!(timeout.timeout, 5);
+    let timeout: Timeout = serde_json::from_str("{}").unwrap();
+    assert_eq!(timeout.timeout, 0);
+}
+
+#[test]
+fn test_deserialize_with_errors() {
+    let document = serde_json::json!({
+        "spec": {
+            "timeout": "soon",
+            "color": "0xff8800",
+            "steps": [],
+            "palette": {},
+        },
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Job>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'timeout' failed at '.spec.timeout': invalid duration: "soon""#
+    );
+}
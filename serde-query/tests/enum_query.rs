@@ -0,0 +1,82 @@
+use serde_query::{Deserialize, DeserializeQuery, Query};
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum Release {
+    V2 {
+        #[query(".data.release.tag")]
+        tag: String,
+        #[query(".data.release.assets[].url")]
+        assets: Vec<String>,
+    },
+    V1(
+        #[query(".tag_name")] String,
+        #[query(".assets[].browser_download_url")] Vec<String>,
+    ),
+    Unknown,
+}
+
+#[test]
+fn test_enum() {
+    let document = serde_json::json!({
+        "data": { "release": { "tag": "v2.0", "assets": [{ "url": "a.tar.gz" }] } },
+    })
+    .to_string();
+    let release: Release = serde_json::from_str(&document).unwrap();
+    assert_eq!(
+        release,
+        Release::V2 {
+            tag: "v2.0".into(),
+            assets: vec!["a.tar.gz".into()],
+        }
+    );
+
+    let document = serde_json::json!({
+        "tag_name": "v1.0",
+        "assets": [{ "browser_download_url": "b.zip" }],
+    })
+    .to_string();
+    let release: Release = serde_json::from_str(&document).unwrap();
+    assert_eq!(release, Release::V1("v1.0".into(), vec!["b.zip".into()]));
+
+    let release: Release = serde_json::from_str("[]").unwrap();
+    assert_eq!(release, Release::Unknown);
+}
+
+#[derive(Debug, DeserializeQuery)]
+enum Version {
+    Number(#[query(".version")] u32),
+    Parts {
+        #[query(".major")]
+        major: u32,
+        #[query(".minor")]
+        minor: u32,
+    },
+}
+
+#[test]
+fn test_enum_errors() {
+    let version: Version = serde_json::from_str::<Query<Version>>(r#"{ "version": 3 }"#)
+        .unwrap()
+        .into();
+    assert!(matches!(version, Version::Number(3)));
+
+    let version: Version = serde_json::from_str::<Query<Version>>(r#"{ "major": 1, "minor": 2 }"#)
+        .unwrap()
+        .into();
+    assert!(matches!(version, Version::Parts { major: 1, minor: 2 }));
+
+    let error = serde_json::from_str::<Query<Version>>(r#"{ "version": "3" }"#)
+        .err()
+        .unwrap();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+No variant of 'Version' matched
+  Number: Query for field '0' failed at '.version': invalid type: string "3", expected u32
+  Parts: Queries failed for fields: 'major', 'minor'
+    1. Query for field 'major' failed at '.': missing field 'major'
+    2. Query for field 'minor' failed at '.': missing field 'minor'
+
+"#
+    );
+}
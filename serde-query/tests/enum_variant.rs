@@ -0,0 +1,235 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Event {
+    Push { r#ref: String, commits: u32 },
+    Release { tag: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct Push {
+    #[query("!Push.ref")]
+    reference: String,
+    #[query("!Push.commits")]
+    commits: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaybeRelease {
+    #[query("!Release?.tag")]
+    tag: Option<String>,
+}
+
+#[test]
+fn test_enum_variant_yaml() {
+    let document = serde_yaml::to_string(&Event::Push {
+        r#ref: "refs/heads/main".into(),
+        commits: 3,
+    })
+    .unwrap();
+    assert!(document.starts_with("!Push"));
+
+    let push: Push = serde_yaml::from_str(&document).unwrap();
+    assert_eq!(push.reference, "refs/heads/main");
+    assert_eq!(push.commits, 3);
+
+    let release: MaybeRelease = serde_yaml::from_str(&document).unwrap();
+    assert_eq!(release.tag, None);
+
+    let document = serde_yaml::to_string(&Event::Release {
+        tag: "v0.2.0".into(),
+    })
+    .unwrap();
+    let release: MaybeRelease = serde_yaml::from_str(&document).unwrap();
+    assert_eq!(release.tag.as_deref(), Some("v0.2.0"));
+
+    let error = serde_yaml::from_str::<Push>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: 'commits', 'reference'
+  1. Query for field 'commits' failed at '.': expected variant 'Push', found variant 'Release'
+  2. Query for field 'reference' failed at '.': expected variant 'Push', found variant 'Release'
+
+"#
+    );
+}
+
+#[test]
+fn test_enum_variant_json() {
+    // externally tagged enums are maps in JSON
+    let document = serde_json::to_string(&Event::Push {
+        r#ref: "refs/heads/main".into(),
+        commits: 3,
+    })
+    .unwrap();
+
+    let push: Push = serde_json::from_str(&document).unwrap();
+    assert_eq!(push.reference, "refs/heads/main");
+    assert_eq!(push.commits, 3);
+
+    let release: MaybeRelease = serde_json::from_str(&document).unwrap();
+    assert_eq!(release.tag, None);
+
+    // a plain field step still reads the map
+    #[derive(Debug, Deserialize)]
+    struct Field {
+        #[query(".Push.ref")]
+        reference: String,
+    }
+    let field: Field = serde_json::from_str(&document).unwrap();
+    assert_eq!(field.reference, "refs/heads/main");
+    // the enum itself may be null
+    #[derive(Debug, Deserialize)]
+    struct Payload {
+        #[query(".event?!Push.ref")]
+        reference: Option<String>,
+    }
+    let payload: Payload =
+        serde_json::from_str(&format!(r#"{{ "event": {} }}"#, document)).unwrap();
+    assert_eq!(payload.reference.as_deref(), Some("refs/heads/main"));
+    let payload: Payload = serde_json::from_str(r#"{ "event": null }"#).unwrap();
+    assert_eq!(payload.reference, None);
+}
+
+#[test]
+fn test_enum_variant_field() {
+    // `.Variant` steps into a variant as well as into a field
+    #[derive(Debug, Deserialize)]
+    struct Push {
+        #[query(".Push.ref")]
+        reference: String,
+        #[query(".Push.commits")]
+        commits: u32,
+    }
+
+    let event = Event::Push {
+        r#ref: "refs/heads/main".into(),
+        commits: 3,
+    };
+    let document = serde_yaml::to_string(&event).unwrap();
+    let push: Push = serde_yaml::from_str(&document).unwrap();
+    assert_eq!(push.reference, "refs/heads/main");
+    assert_eq!(push.commits, 3);
+
+    let document = serde_json::to_string(&event).unwrap();
+    let push: Push = serde_json::from_str(&document).unwrap();
+    assert_eq!(push.reference, "refs/heads/main");
+    assert_eq!(push.commits, 3);
+
+    let document = serde_yaml::to_string(&Event::Release {
+        tag: "v0.2.0".into(),
+    })
+    .unwrap();
+    let error = serde_yaml::from_str::<Push>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: 'commits', 'reference'
+  1. Query for field 'commits' failed at '.': expected variant 'Push', found variant 'Release'
+  2. Query for field 'reference' failed at '.': expected variant 'Push', found variant 'Release'
+
+"#
+    );
+}
+
+#[test]
+fn test_enum_variant_ron() {
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Circle { r: f64 },
+        Rect(f64, f64),
+        Named(String),
+        Group { name: String, first: Box<Shape> },
+        Empty,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Circle {
+        #[query("!Circle.r")]
+        r: f64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Rect {
+        #[query("!Rect.[1]")]
+        height: f64,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Named {
+        #[query("!Named")]
+        name: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Group {
+        #[query("!Group.name")]
+        name: String,
+        #[query("!Group.first!Circle.r")]
+        r: f64,
+    }
+
+    let document = ron::to_string(&Shape::Circle { r: 1.5 }).unwrap();
+    assert_eq!(document, "Circle(r:1.5)");
+    let circle: Circle = ron::from_str(&document).unwrap();
+    assert_eq!(circle.r, 1.5);
+
+    let document = ron::to_string(&Shape::Rect(2.0, 3.0)).unwrap();
+    let rect: Rect = ron::from_str(&document).unwrap();
+    assert_eq!(rect.height, 3.0);
+
+    let document = ron::to_string(&Shape::Named("hexagon".into())).unwrap();
+    let named: Named = ron::from_str(&document).unwrap();
+    assert_eq!(named.name, "hexagon");
+
+    let document = ron::to_string(&Shape::Group {
+        name: "wheels".into(),
+        first: Box::new(Shape::Circle { r: 0.5 }),
+    })
+    .unwrap();
+    let group: Group = ron::from_str(&document).unwrap();
+    assert_eq!(group.name, "wheels");
+    assert_eq!(group.r, 0.5);
+
+    // another variant is reported before its payload is read
+    let document = ron::to_string(&Shape::Named("hexagon".into())).unwrap();
+    let error = ron::from_str::<Circle>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"1:6: Query for field 'r' failed at '.': expected variant 'Circle', found variant 'Named'"#
+    );
+    let document = ron::to_string(&Shape::Empty).unwrap();
+    let error = ron::from_str::<Circle>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"1:6: Query for field 'r' failed at '.': expected variant 'Circle', found variant 'Empty'"#
+    );
+}
+
+#[test]
+fn test_enum_variant_buffered() {
+    // negative indices buffer the elements, including the tagged values of YAML
+    #[derive(Debug, Deserialize)]
+    struct Events {
+        #[query(".[-1]")]
+        last: Event,
+        #[query(".[0]")]
+        first: Event,
+    }
+
+    let events = [
+        Event::Release {
+            tag: "v0.1.0".into(),
+        },
+        Event::Push {
+            r#ref: "refs/heads/main".into(),
+            commits: 3,
+        },
+    ];
+    let document = serde_yaml::to_string(&events).unwrap();
+
+    let Events { last, first } = serde_yaml::from_str(&document).unwrap();
+    assert_eq!(last, events[1]);
+    assert_eq!(first, events[0]);
+}
@@ -19,3 +19,37 @@ fn test_field_name() {
 
     assert_eq!(data.with_space, 42);
 }
+
+#[test]
+fn test_field_name_escape() {
+    use serde_query::{DeserializeQuery, Query};
+
+    #[derive(DeserializeQuery)]
+    struct Data {
+        #[query(r#".["line\nbreak"]"#)]
+        line_break: i64,
+        #[query(r#"."café""#)]
+        cafe: i64,
+        #[query(r#".["😀"]"#)]
+        emoji: i64,
+        #[query(r#".["quote\"and\\backslash"]"#)]
+        quote: i64,
+    }
+
+    let document = serde_json::json!({
+        "line\nbreak": 1,
+        "café": 2,
+        "😀": 3,
+        "quote\"and\\backslash": 4,
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str::<Query<Data>>(&document)
+        .unwrap()
+        .into();
+
+    assert_eq!(data.line_break, 1);
+    assert_eq!(data.cafe, 2);
+    assert_eq!(data.emoji, 3);
+    assert_eq!(data.quote, 4);
+}
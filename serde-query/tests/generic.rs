@@ -0,0 +1,104 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use serde::{Deserialize as _, Deserializer};
+
+use serde_query::{Deserialize, DeserializeQuery, Query};
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[query(".data.items[]")]
+    items: Vec<T>,
+    #[query(".meta.next")]
+    next: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, PartialEq)]
+struct Item {
+    id: u64,
+}
+
+#[test]
+fn test_generic() {
+    let document = serde_json::json!({
+        "data": { "items": [{ "id": 1 }, { "id": 2 }] },
+        "meta": { "next": "abc" },
+    })
+    .to_string();
+
+    let page: Page<Item> = serde_json::from_str(&document).unwrap();
+    assert_eq!(page.items, [Item { id: 1 }, Item { id: 2 }]);
+    assert_eq!(page.next.as_deref(), Some("abc"));
+
+    let page: Page<serde_json::Value> = serde_json::from_str(&document).unwrap();
+    assert_eq!(page.items.len(), 2);
+}
+
+#[derive(DeserializeQuery)]
+struct Pair<K, V = String, const N: usize = 2>
+where
+    K: Debug,
+{
+    #[query(".keys[]")]
+    keys: Vec<K>,
+    #[query(".values[0]")]
+    first: V,
+    #[query(".values[1]")]
+    second: V,
+}
+
+#[test]
+fn test_generic_query() {
+    let document = serde_json::json!({
+        "keys": [1, 2],
+        "values": ["a", "b"],
+    })
+    .to_string();
+
+    let pair: Pair<u8> = serde_json::from_str::<Query<Pair<u8>>>(&document)
+        .unwrap()
+        .into();
+    assert_eq!(pair.keys, [1, 2]);
+    assert_eq!(pair.first, "a");
+    assert_eq!(pair.second, "b");
+}
+
+/// An identifier of `T`, which does not need to implement `Deserialize`.
+#[derive(Debug)]
+struct Id<T>(u64, PhantomData<T>);
+
+fn id<'de, D, T>(deserializer: D) -> Result<Id<T>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    u64::deserialize(deserializer).map(|id| Id(id, PhantomData))
+}
+
+#[derive(Debug, Deserialize)]
+struct Owner<T> {
+    #[query(".id", deserialize_with = "id")]
+    id: Id<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository<T> {
+    #[query(".id", deserialize_with = "id")]
+    id: Id<T>,
+    #[query(".owner", nested)]
+    owner: Owner<T>,
+}
+
+#[test]
+fn test_generic_deserialize_with() {
+    // no `T: Deserialize` bound is added for `deserialize_with` and `nested` fields
+    struct User;
+
+    let document = serde_json::json!({
+        "id": 1,
+        "owner": { "id": 2 },
+    })
+    .to_string();
+
+    let repository: Repository<User> = serde_json::from_str(&document).unwrap();
+    assert_eq!(repository.id.0, 1);
+    assert_eq!(repository.owner.id.0, 2);
+}
@@ -0,0 +1,87 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[query(implicit)]
+struct Repository {
+    name: String,
+    r#type: String,
+    #[query(".owner.login")]
+    owner: String,
+    stargazers_count: u64,
+}
+
+#[test]
+fn test_implicit() {
+    let document = serde_json::json!({
+        "name": "serde-query",
+        "type": "public",
+        "owner": { "login": "pandaman64" },
+        "stargazers_count": 42,
+    })
+    .to_string();
+
+    let repository: Repository = serde_json::from_str(&document).unwrap();
+    assert_eq!(repository.name, "serde-query");
+    assert_eq!(repository.r#type, "public");
+    assert_eq!(repository.owner, "pandaman64");
+    assert_eq!(repository.stargazers_count, 42);
+}
+
+#[test]
+fn test_implicit_rename_all() {
+    #[derive(Debug, Deserialize)]
+    #[query(implicit, rename_all = "camelCase")]
+    struct Release {
+        tag_name: String,
+        html_url: String,
+        #[query(".assets[].name")]
+        asset_names: Vec<String>,
+    }
+
+    let document = serde_json::json!({
+        "tagName": "v0.2.0",
+        "htmlUrl": "https://example.com",
+        "assets": [{ "name": "a.tar.gz" }],
+    })
+    .to_string();
+
+    let release: Release = serde_json::from_str(&document).unwrap();
+    assert_eq!(release.tag_name, "v0.2.0");
+    assert_eq!(release.html_url, "https://example.com");
+    assert_eq!(release.asset_names, ["a.tar.gz"]);
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    #[query(implicit, rename_all = "kebab-case")]
+    struct Package {
+        package_name: String,
+    }
+
+    let error = serde_json::from_str::<Package>(r#"{ "package_name": "x" }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'package_name' failed at '.': missing field 'package-name'"#
+    );
+}
+
+#[test]
+fn test_implicit_enum() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[query(tag = ".kind", implicit, rename_all = "SCREAMING_SNAKE_CASE")]
+    enum Shape {
+        #[query(tag_value = "circle")]
+        Circle { radius: f64 },
+        #[query(tag_value = "rect")]
+        Rect { width: f64, height: f64 },
+    }
+
+    let shape: Shape =
+        serde_json::from_str(r#"{ "kind": "rect", "WIDTH": 2, "HEIGHT": 3 }"#).unwrap();
+    assert_eq!(
+        shape,
+        Shape::Rect {
+            width: 2.0,
+            height: 3.0
+        }
+    );
+}
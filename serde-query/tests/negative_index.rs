@@ -0,0 +1,113 @@
+use serde_query::Deserialize;
+
+#[test]
+fn test_negative_index() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".commits.[-1].sha")]
+        latest: String,
+        #[query(".commits.[-2].sha")]
+        second_latest: String,
+        #[query(".commits.[0].sha")]
+        first: String,
+    }
+
+    let document = serde_json::json!({
+        "commits": [
+            { "sha": "a" },
+            { "sha": "b" },
+            { "sha": "c" },
+        ],
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str(&document).unwrap();
+    assert_eq!(data.latest, "c");
+    assert_eq!(data.second_latest, "b");
+    assert_eq!(data.first, "a");
+}
+
+#[test]
+fn test_negative_index_same_element() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".[0].x")]
+        first: i64,
+        #[query(".[-1].y")]
+        last: i64,
+    }
+
+    let document = serde_json::json!([{ "x": 1, "y": 2 }]);
+
+    let data: Data = serde_json::from_str(&document.to_string()).unwrap();
+    assert_eq!(data.first, 1);
+    assert_eq!(data.last, 2);
+
+    // `Value` knows the length up front, and the element is buffered for both queries
+    let data = <Data as serde::Deserialize>::deserialize(document).unwrap();
+    assert_eq!(data.first, 1);
+    assert_eq!(data.last, 2);
+}
+
+#[test]
+fn test_negative_index_streamed() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".[0]")]
+        first: u32,
+        #[query(".[1]")]
+        second: u32,
+        #[query(".[-1]")]
+        last: u32,
+    }
+
+    // bincode knows the length up front, and cannot buffer elements without their types
+    let document = bincode::serialize(&vec![10u32, 20, 30]).unwrap();
+
+    let data: Data = bincode::deserialize(&document).unwrap();
+    assert_eq!(data.first, 10);
+    assert_eq!(data.second, 20);
+    assert_eq!(data.last, 30);
+}
+
+#[test]
+fn test_negative_index_128_bit() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".[-1]")]
+        last: u128,
+        #[query(".[-2]")]
+        second_last: i128,
+    }
+
+    let document =
+        "[-170141183460469231731687303715884105728, 340282366920938463463374607431768211455]";
+
+    let data: Data = serde_yaml::from_str(document).unwrap();
+    assert_eq!(data.last, u128::MAX);
+    assert_eq!(data.second_last, i128::MIN);
+}
+
+#[test]
+fn test_negative_index_errors() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".[-3]")]
+        _third_last: i64,
+        #[query(".[-1]")]
+        _last: i64,
+    }
+
+    let document = serde_json::json!([1, "two"]).to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: '_last', '_third_last'
+  1. Query for field '_last' failed at '.[-1]': invalid type: string "two", expected i64
+  2. Query for field '_third_last' failed at '.': the sequence must have at least 3 elements
+
+"#
+    );
+}
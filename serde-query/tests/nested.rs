@@ -0,0 +1,91 @@
+use serde_query::{Deserialize, DeserializeQuery, Query};
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    #[query(".name")]
+    name: String,
+    #[query(".date")]
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitInfo {
+    #[query(".message")]
+    message: String,
+    #[query(".author", nested)]
+    author: Author,
+}
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    #[query(".sha")]
+    sha: String,
+    #[query(".commit", nested)]
+    commit: CommitInfo,
+    #[query(".parents[]", nested)]
+    parents: Vec<Parent>,
+}
+
+#[derive(Debug, DeserializeQuery)]
+struct Parent {
+    #[query(".sha")]
+    sha: String,
+}
+
+#[test]
+fn test_nested() {
+    let document = serde_json::json!({
+        "sha": "abc",
+        "commit": {
+            "message": "Initial commit",
+            "author": { "name": "Kou", "date": "2020-08-22" },
+        },
+        "parents": [{ "sha": "def" }, { "sha": "ghi" }],
+    })
+    .to_string();
+
+    let commit: Commit = serde_json::from_str(&document).unwrap();
+    assert_eq!(commit.sha, "abc");
+    assert_eq!(commit.commit.message, "Initial commit");
+    assert_eq!(commit.commit.author.name, "Kou");
+    assert_eq!(commit.commit.author.date, "2020-08-22");
+    let parents: Vec<&str> = commit.parents.iter().map(|p| p.sha.as_str()).collect();
+    assert_eq!(parents, ["def", "ghi"]);
+
+    // the nested structs are usable on their own
+    let parent: Parent = serde_json::from_str::<Query<Parent>>(r#"{"sha": "def"}"#)
+        .unwrap()
+        .into();
+    assert_eq!(parent.sha, "def");
+}
+
+#[test]
+fn test_nested_errors() {
+    let document = serde_json::json!({
+        "sha": "abc",
+        "commit": {
+            "author": { "name": 42, "date": "2020-08-22" },
+        },
+        "parents": [{ "sha": "def" }, {}],
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Commit>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: 'commit.author.name', 'commit.message', 'parents.sha'
+  1. Query for field 'commit.author.name' failed at '.commit.author.name': invalid type: integer `42`, expected a string at line 1 column 50
+  2. Query for field 'commit.message' failed at '.commit': missing field 'message'
+  3. Query for field 'parents.sha' failed at '.parents.[]': missing field 'sha'
+
+"#
+    );
+
+    let document = serde_json::json!({ "sha": "abc", "commit": "abc", "parents": [] }).to_string();
+    let error = serde_json::from_str::<Commit>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'commit' failed at '.commit': invalid type: string "abc", expected one of the following fields: 'author', or 'message' at line 1 column 15"#
+    );
+}
@@ -0,0 +1,168 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    #[query(".author?.name")]
+    author_name: Option<String>,
+    #[query(".message?")]
+    message: Option<String>,
+    #[query(".parents?.[].sha")]
+    parents: Option<Vec<String>>,
+    #[query(".tags.[0]?")]
+    first_tag: Option<String>,
+}
+
+#[test]
+fn test_optional_present() {
+    let document = serde_json::json!({
+        "author": { "name": "Kou" },
+        "message": "Initial commit",
+        "parents": [{ "sha": "abc" }, { "sha": "def" }],
+        "tags": ["v0.1.0"],
+    })
+    .to_string();
+
+    let commit: Commit = serde_json::from_str(&document).unwrap();
+    assert_eq!(commit.author_name.as_deref(), Some("Kou"));
+    assert_eq!(commit.message.as_deref(), Some("Initial commit"));
+    assert_eq!(commit.parents, Some(vec!["abc".into(), "def".into()]));
+    assert_eq!(commit.first_tag.as_deref(), Some("v0.1.0"));
+}
+
+#[test]
+fn test_optional_missing() {
+    let document = serde_json::json!({
+        "tags": [],
+    })
+    .to_string();
+
+    let commit: Commit = serde_json::from_str(&document).unwrap();
+    assert_eq!(commit.author_name, None);
+    assert_eq!(commit.message, None);
+    assert_eq!(commit.parents, None);
+    assert_eq!(commit.first_tag, None);
+}
+
+#[test]
+fn test_optional_null() {
+    let document = serde_json::json!({
+        "author": null,
+        "message": null,
+        "parents": null,
+        "tags": [null],
+    })
+    .to_string();
+
+    let commit: Commit = serde_json::from_str(&document).unwrap();
+    assert_eq!(commit.author_name, None);
+    assert_eq!(commit.message, None);
+    assert_eq!(commit.parents, None);
+    assert_eq!(commit.first_tag, None);
+}
+
+#[test]
+fn test_optional_errors() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        // only the author is optional
+        #[query(".author?.name")]
+        _author_name: Option<String>,
+        // type errors are still reported
+        #[query(".count?")]
+        _count: Option<i64>,
+        // the non-optional query is still required
+        #[query(".author.email")]
+        _author_email: String,
+    }
+
+    let document = serde_json::json!({
+        "author": {},
+        "count": "many",
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: '_author_email', '_author_name', '_count'
+  1. Query for field '_author_email' failed at '.author': missing field 'email'
+  2. Query for field '_author_name' failed at '.author': missing field 'name'
+  3. Query for field '_count' failed at '.count': invalid type: string "many", expected i64 at line 1 column 27
+
+"#
+    );
+
+    let document = serde_json::json!({
+        "author": null,
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '_author_email' failed at '.author': invalid type: null, expected one of the following fields: 'email', or 'name'"#
+    );
+}
+
+#[test]
+fn test_optional_collection() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".labels[]?.name")]
+        labels: Vec<String>,
+        #[query(".env[]?")]
+        env: Vec<String>,
+        #[query(".matrix[1:]?[0]")]
+        first_column: Vec<i64>,
+        #[query(".reviewers?[]?")]
+        reviewers: Option<Vec<String>>,
+    }
+
+    let document = serde_json::json!({
+        "labels": [{ "name": "bug" }],
+        "env": { "CI": "true" },
+        "matrix": [[1, 2], [3, 4]],
+        "reviewers": ["alice"],
+    })
+    .to_string();
+
+    let data = serde_json::from_str::<Data>(&document).unwrap();
+    assert_eq!(data.labels, ["bug"]);
+    assert_eq!(data.env, ["true"]);
+    assert_eq!(data.first_column, [3]);
+    assert_eq!(data.reviewers, Some(vec!["alice".into()]));
+
+    // missing collections are empty, and `?` on the field takes precedence
+    let data = serde_json::from_str::<Data>("{}").unwrap();
+    assert!(data.labels.is_empty());
+    assert!(data.env.is_empty());
+    assert!(data.first_column.is_empty());
+    assert_eq!(data.reviewers, None);
+
+    let document = serde_json::json!({
+        "labels": null,
+        "env": null,
+        "matrix": null,
+        "reviewers": null,
+    })
+    .to_string();
+
+    let data = serde_json::from_str::<Data>(&document).unwrap();
+    assert!(data.labels.is_empty());
+    assert!(data.env.is_empty());
+    assert!(data.first_column.is_empty());
+    assert_eq!(data.reviewers, None);
+
+    // other errors are still reported
+    let document = serde_json::json!({
+        "labels": "bug",
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"invalid type: string "bug", expected a sequence or a map at line 1 column 15"#
+    );
+}
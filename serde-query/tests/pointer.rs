@@ -0,0 +1,71 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    #[query("/data/items/0/id")]
+    first_id: u64,
+    #[query("/data/a~1b")]
+    slashed: String,
+    #[query("/data/name", syntax = "pointer")]
+    name: String,
+    #[query("$.data.items[1].id")]
+    second_id: u64,
+    #[query("$.data.groups.*.tags[0]")]
+    first_tags: Vec<String>,
+    #[query("$['data']['first-name']", syntax = "jsonpath")]
+    first_name: String,
+}
+
+#[test]
+fn test_pointer_and_jsonpath() {
+    let document = serde_json::json!({
+        "data": {
+            "items": [
+                { "id": 1 },
+                { "id": 2 },
+            ],
+            "groups": {
+                "x": { "tags": ["a", "b"] },
+                "y": { "tags": ["c"] },
+            },
+            "a/b": "slashed",
+            "name": "kou",
+            "first-name": "Kou",
+        }
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str(&document).unwrap();
+    assert_eq!(data.first_id, 1);
+    assert_eq!(data.slashed, "slashed");
+    assert_eq!(data.name, "kou");
+    assert_eq!(data.second_id, 2);
+    assert_eq!(data.first_tags, ["a", "c"]);
+    assert_eq!(data.first_name, "Kou");
+}
+
+#[test]
+fn test_pointer_error() {
+    let document = serde_json::json!({
+        "data": {
+            "items": [{ "id": "1" }],
+            "groups": { "x": { "tags": [] } },
+            "a/b": "slashed",
+            "name": "kou",
+            "first-name": "Kou",
+        }
+    })
+    .to_string();
+
+    let snapshot = format!("{:?}", serde_json::from_str::<Data>(&document));
+    k9::snapshot!(
+        snapshot,
+        r#"
+Err(Error("Queries failed for fields: 'first_id', 'first_tags', 'second_id'\
+  1. Query for field 'first_id' failed at '.data.items.[0].id': invalid type: string \\"1\\", expected u64 at line 1 column 89\
+  2. Query for field 'first_tags' failed at '.data.groups.[].tags': the sequence must have at least 1 elements\
+  3. Query for field 'second_id' failed at '.data.items': the sequence must have at least 2 elements\
+", line: 0, column: 0))
+"#
+    );
+}
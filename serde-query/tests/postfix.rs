@@ -0,0 +1,43 @@
+#[test]
+fn test_postfix() {
+    use serde_query::{DeserializeQuery, Query};
+
+    #[derive(DeserializeQuery)]
+    struct Data {
+        #[query(".commits[0].sha")]
+        first_sha: String,
+        #[query(r#".commits[-1]["author"].login"#)]
+        last_author: String,
+        #[query(".tags[].name")]
+        tags: Vec<String>,
+        #[query(".matrix[1:][0]")]
+        first_column: Vec<i64>,
+        #[query(r#"."field name with spaces""#)]
+        with_space: i64,
+        #[query(".reviewers[0]?.login")]
+        first_reviewer: Option<String>,
+    }
+
+    let document = serde_json::json!({
+        "commits": [
+            { "sha": "aaa", "author": { "login": "alice" } },
+            { "sha": "bbb", "author": { "login": "bob" } },
+        ],
+        "tags": [{ "name": "v0.1" }, { "name": "v0.2" }],
+        "matrix": [[1, 2], [3, 4], [5, 6]],
+        "field name with spaces": 42,
+        "reviewers": [null],
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str::<Query<Data>>(&document)
+        .unwrap()
+        .into();
+
+    assert_eq!(data.first_sha, "aaa");
+    assert_eq!(data.last_author, "bob");
+    assert_eq!(data.tags, ["v0.1", "v0.2"]);
+    assert_eq!(data.first_column, [3, 5]);
+    assert_eq!(data.with_space, 42);
+    assert_eq!(data.first_reviewer, None);
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+
+use serde_query::Deserialize;
+
+#[test]
+fn test_recursive_descent() {
+    #[derive(Debug, Deserialize)]
+    struct Versions {
+        #[query("..version")]
+        versions: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Images {
+        #[query(".services..image.name")]
+        images: BTreeSet<String>,
+    }
+
+    let document = r#"
+{
+    "version": "3",
+    "services": {
+        "web": {
+            "image": { "name": "nginx", "tag": "1.25" },
+            "ports": [80, 443]
+        },
+        "db": {
+            "image": { "name": "postgres" },
+            "volumes": [{ "version": "2" }]
+        }
+    },
+    "metadata": null
+}
+"#;
+
+    let versions: Versions = serde_json::from_str(document).unwrap();
+    assert_eq!(versions.versions, vec!["3", "2"]);
+
+    let images: Images = serde_json::from_str(document).unwrap();
+    assert_eq!(
+        images.images,
+        BTreeSet::from(["nginx".to_string(), "postgres".to_string()])
+    );
+}
+
+#[test]
+fn test_recursive_descent_error() {
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        #[query("..version")]
+        _versions: Vec<String>,
+    }
+
+    let document = serde_json::json!({
+        "a": { "version": "1" },
+        "b": [{ "version": 2 }],
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Config>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '_versions' failed at '..version': invalid type: integer `2`, expected a string at line 1 column 38"#
+    );
+}
@@ -0,0 +1,61 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[query(root = ".data.attributes")]
+struct Article {
+    #[query(".title")]
+    title: String,
+    #[query(".tags[]")]
+    tags: Vec<String>,
+    #[query(".body // null")]
+    body: Option<String>,
+}
+
+#[test]
+fn test_root() {
+    let document = serde_json::json!({
+        "data": {
+            "type": "articles",
+            "attributes": { "title": "JSON:API", "tags": ["json", "api"] },
+        },
+    })
+    .to_string();
+
+    let article: Article = serde_json::from_str(&document).unwrap();
+    assert_eq!(article.title, "JSON:API");
+    assert_eq!(article.tags, ["json", "api"]);
+    assert_eq!(article.body, None);
+}
+
+#[test]
+fn test_root_implicit() {
+    #[derive(Debug, Deserialize)]
+    #[query(root = r#".data.["viewer"]"#, implicit, rename_all = "camelCase")]
+    struct Viewer {
+        login: String,
+        avatar_url: String,
+    }
+
+    let document = serde_json::json!({
+        "data": { "viewer": { "login": "octocat", "avatarUrl": "https://example.com" } },
+    })
+    .to_string();
+
+    let viewer: Viewer = serde_json::from_str(&document).unwrap();
+    assert_eq!(viewer.login, "octocat");
+    assert_eq!(viewer.avatar_url, "https://example.com");
+}
+
+#[test]
+fn test_root_errors() {
+    let document = serde_json::json!({
+        "data": { "attributes": { "title": 42, "tags": [] } },
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Article>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'title' failed at '.data.attributes.title': invalid type: integer `42`, expected a string at line 1 column 43"#
+    );
+}
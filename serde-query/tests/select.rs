@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use serde_query::Deserialize;
+
+#[test]
+fn test_select() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(r#".items.[] | select(.kind == "Deployment") | .metadata.name"#)]
+        deployments: Vec<String>,
+        #[query(r#".items.[] | select(.kind != "Deployment") | .metadata.name"#)]
+        others: Vec<String>,
+        #[query(r#".items.[] | select(.spec.replicas >= 2) | .metadata.name"#)]
+        replicated: Vec<String>,
+        #[query(r#".items.[] | select(.spec.paused) | .metadata.name"#)]
+        paused: Vec<String>,
+        #[query(r#".labels.{} | select(. != null)"#)]
+        labels: BTreeMap<String, String>,
+    }
+
+    // the kind comes after the name
+    let document = r#"
+{
+    "items": [
+        { "metadata": { "name": "web" }, "kind": "Deployment", "spec": { "replicas": 3 } },
+        { "metadata": { "name": "db" }, "kind": "StatefulSet", "spec": { "replicas": 1, "paused": true } },
+        { "metadata": { "name": "worker" }, "kind": "Deployment", "spec": { "replicas": 2, "paused": false } }
+    ],
+    "labels": { "app": "shop", "tier": null }
+}
+"#;
+
+    let data: Data = serde_json::from_str(document).unwrap();
+    assert_eq!(data.deployments, vec!["web", "worker"]);
+    assert_eq!(data.others, vec!["db"]);
+    assert_eq!(data.replicated, vec!["web", "worker"]);
+    assert_eq!(data.paused, vec!["db"]);
+    assert_eq!(
+        data.labels,
+        BTreeMap::from([("app".to_string(), "shop".to_string())])
+    );
+}
+
+#[test]
+fn test_select_large_integers() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(r#".items.[] | select(.id == 9007199254740993) | .name"#)]
+        equal: Vec<String>,
+        #[query(r#".items.[] | select(.id > 9007199254740992) | .name"#)]
+        greater: Vec<String>,
+        #[query(r#".items.[] | select(.id > 9007199254740992.0) | .name"#)]
+        greater_than_float: Vec<String>,
+    }
+
+    // 2^53 + 1 is not representable as f64
+    let document = r#"
+{
+    "items": [
+        { "id": 9007199254740992, "name": "a" },
+        { "id": 9007199254740993, "name": "b" }
+    ]
+}
+"#;
+
+    let data: Data = serde_json::from_str(document).unwrap();
+    assert_eq!(data.equal, vec!["b"]);
+    assert_eq!(data.greater, vec!["b"]);
+    // compared as f64 if either side is a float, where both ids are 2^53
+    assert!(data.greater_than_float.is_empty());
+}
+
+#[test]
+fn test_select_errors() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(r#".items.[] | select(.enabled) | .id"#)]
+        _ids: Vec<u32>,
+    }
+
+    let document = serde_json::json!({
+        "items": [
+            { "enabled": true, "id": "one" },
+            { "enabled": false, "id": "two" },
+        ],
+    })
+    .to_string();
+
+    let error = serde_json::from_str::<Data>(&document).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '_ids' failed at '.items.[] | select(.enabled).id': invalid type: string "one", expected u32"#
+    );
+}
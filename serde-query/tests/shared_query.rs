@@ -0,0 +1,79 @@
+use std::borrow::Cow;
+
+use serde_query::Deserialize;
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Version {
+    major: u64,
+    minor: u64,
+}
+
+fn parse_version<'de, D>(deserializer: D) -> Result<Version, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+    let (major, minor) = s
+        .split_once('.')
+        .ok_or_else(|| serde::de::Error::custom("expected a version"))?;
+    Ok(Version {
+        major: major.parse().map_err(serde::de::Error::custom)?,
+        minor: minor.parse().map_err(serde::de::Error::custom)?,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct Package<'a> {
+    #[query(".version")]
+    raw_version: String,
+    #[query(".version", deserialize_with = "parse_version")]
+    version: Version,
+    #[query(".version")]
+    borrowed_version: Cow<'a, str>,
+    #[query(".dependencies[].name")]
+    dependency_names: Vec<String>,
+    #[query(".dependencies[].name")]
+    unique_dependencies: std::collections::HashSet<String>,
+    #[query(".keywords | length")]
+    keyword_count: usize,
+    #[query(".keywords | length")]
+    keyword_count_u8: u8,
+}
+
+#[test]
+fn test_shared_query() {
+    let document = serde_json::json!({
+        "version": "1.2",
+        "dependencies": [{ "name": "serde" }, { "name": "serde" }],
+        "keywords": ["json", "query", "serde"],
+    })
+    .to_string();
+
+    let package: Package = serde_json::from_str(&document).unwrap();
+    assert_eq!(package.raw_version, "1.2");
+    assert_eq!(package.version, Version { major: 1, minor: 2 });
+    assert!(matches!(package.borrowed_version, Cow::Borrowed("1.2")));
+    assert_eq!(package.dependency_names, ["serde", "serde"]);
+    assert_eq!(package.unique_dependencies.len(), 1);
+    assert_eq!(package.keyword_count, 3);
+    assert_eq!(package.keyword_count_u8, 3);
+}
+
+#[test]
+fn test_shared_query_errors() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Release {
+        #[query(".version")]
+        raw_version: String,
+        #[query(".version", deserialize_with = "parse_version")]
+        version: Version,
+    }
+
+    // each field reports its own error
+    let error = serde_json::from_str::<Release>(r#"{"version": "latest"}"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field 'version' failed at '.version': expected a version"#
+    );
+}
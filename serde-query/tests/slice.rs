@@ -0,0 +1,53 @@
+use serde_query::Deserialize;
+
+#[test]
+fn test_slice() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        #[query(".numbers.[2:5]")]
+        middle: Vec<i64>,
+        #[query(".letters.[:3]")]
+        head: Vec<String>,
+        #[query(".commits.[5:].id")]
+        tail_ids: Vec<i64>,
+        #[query(".tags.[1:100]")]
+        out_of_range: Vec<String>,
+    }
+
+    let document = serde_json::json!({
+        "numbers": [0, 1, 2, 3, 4, 5, 6],
+        "letters": ["a", "b", "c", "d"],
+        "tags": ["v0.1.0", "v0.2.0"],
+        "commits": [
+            { "id": 0 },
+            { "id": 1 },
+            { "id": 2 },
+            { "id": 3 },
+            { "id": 4 },
+            { "id": 5 },
+            { "id": 6 },
+        ],
+    })
+    .to_string();
+
+    let data: Data = serde_json::from_str(&document).unwrap();
+    assert_eq!(data.middle, vec![2, 3, 4]);
+    assert_eq!(data.head, vec!["a", "b", "c"]);
+    assert_eq!(data.tail_ids, vec![5, 6]);
+    assert_eq!(data.out_of_range, vec!["v0.2.0"]);
+}
+
+#[test]
+fn test_slice_skips_elements() {
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        // elements outside of the slice are not deserialized
+        #[query(".[1:2]")]
+        second: Vec<String>,
+    }
+
+    let document = serde_json::json!([0, "a", { "b": 2 }]).to_string();
+
+    let data: Data = serde_json::from_str(&document).unwrap();
+    assert_eq!(data.second, vec!["a"]);
+}
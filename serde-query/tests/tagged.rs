@@ -0,0 +1,85 @@
+use serde_query::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[query(tag = ".type")]
+enum Event {
+    #[query(tag_value = "push")]
+    Push {
+        #[query(".payload.ref")]
+        reference: String,
+        #[query(".payload.commits[].sha")]
+        commits: Vec<String>,
+    },
+    #[query(tag_value = "star")]
+    Star(#[query(".actor.login")] String),
+    #[query(tag_value = "ping")]
+    Ping,
+}
+
+#[test]
+fn test_tagged() {
+    // the tag arrives first
+    let document = r#"{
+        "type": "push",
+        "payload": { "ref": "refs/heads/main", "commits": [{ "sha": "aaa" }, { "sha": "bbb" }] }
+    }"#;
+    let event: Event = serde_json::from_str(document).unwrap();
+    assert_eq!(
+        event,
+        Event::Push {
+            reference: "refs/heads/main".into(),
+            commits: vec!["aaa".into(), "bbb".into()],
+        }
+    );
+
+    // the tag arrives after the payload
+    let document = r#"{ "actor": { "login": "alice" }, "id": 1, "type": "star" }"#;
+    let event: Event = serde_json::from_str(document).unwrap();
+    assert_eq!(event, Event::Star("alice".into()));
+
+    let event: Event = serde_json::from_str(r#"{ "type": "ping", "zen": "..." }"#).unwrap();
+    assert_eq!(event, Event::Ping);
+}
+
+#[test]
+fn test_tagged_nested() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[query(tag = ".meta.kinds[1]")]
+    enum Shape {
+        #[query(tag_value = "circle")]
+        Circle(#[query(".r")] f64),
+        #[query(tag_value = "square")]
+        Square(#[query(".side")] f64),
+    }
+
+    let shape: Shape =
+        serde_json::from_str(r#"{ "side": 2, "meta": { "kinds": ["shape", "square"] } }"#).unwrap();
+    assert_eq!(shape, Shape::Square(2.0));
+}
+
+#[test]
+fn test_tagged_errors() {
+    let error = serde_json::from_str::<Event>(r#"{ "type": "fork" }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        "unknown variant `fork`, expected one of `push`, `star`, `ping` at line 1 column 18"
+    );
+
+    let error = serde_json::from_str::<Event>(r#"{ "payload": {} }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"missing the tag '.type' of 'Event' at line 1 column 17"#
+    );
+
+    let error = serde_json::from_str::<Event>(r#"{ "type": 1 }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"the tag '.type' of 'Event' is not a string at line 1 column 13"#
+    );
+
+    let error = serde_json::from_str::<Event>(r#"{ "type": "star", "actor": {} }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"Query for field '0' failed at '.actor': missing field 'login' at line 1 column 31"#
+    );
+}
@@ -0,0 +1,44 @@
+use serde_query::{Deserialize, DeserializeQuery, Query};
+
+#[derive(Debug, Deserialize)]
+struct Point(
+    #[query(".x")] f64,
+    #[query(".y // 0")]
+    f64,
+);
+
+#[derive(Debug, DeserializeQuery)]
+struct Id(#[query(".meta.id")] u64);
+
+#[test]
+fn test_tuple() {
+    let point: Point = serde_json::from_str(r#"{ "x": 1.5, "y": -2 }"#).unwrap();
+    assert_eq!(point.0, 1.5);
+    assert_eq!(point.1, -2.0);
+
+    let point: Point = serde_json::from_str(r#"{ "x": 1.5 }"#).unwrap();
+    assert_eq!(point.1, 0.0);
+
+    let id: Id = serde_json::from_str::<Query<Id>>(r#"{ "meta": { "id": 42 } }"#)
+        .unwrap()
+        .into();
+    assert_eq!(id.0, 42);
+}
+
+#[test]
+fn test_tuple_errors() {
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Range(#[query(".from")] u8, #[query(".to")] u8);
+
+    let error = serde_json::from_str::<Range>(r#"{ "from": "north" }"#).unwrap_err();
+    k9::snapshot!(
+        error.to_string(),
+        r#"
+Queries failed for fields: '0', '1'
+  1. Query for field '0' failed at '.from': invalid type: string "north", expected u8 at line 1 column 17
+  2. Query for field '1' failed at '.': missing field 'to'
+
+"#
+    );
+}